@@ -30,7 +30,7 @@ impl Backend for FehBackend {
         let status = Command::new("feh")
             .args(["--bg-center", wallpaper_path])
             .status()
-            .map_err(|_| ("Failed to execute feh"))?;
+            .map_err(|_| "Failed to execute feh")?;
         if status.success() {
             Ok(())
         } else {
@@ -43,7 +43,7 @@ impl Backend for SwwwBackend {
             // TODO: Make args configurable and remove hardcoded values
             .args(["img", wallpaper_path, "-t", "fade"])
             .status()
-            .map_err(|_| ("Failed to execute swww"))?;
+            .map_err(|_| "Failed to execute swww")?;
         if status.success() {
             Ok(())
         } else {
@@ -0,0 +1,400 @@
+use crate::config::{Config, RandomScope};
+use crate::core::{
+    rotation,
+    state::{self, RandomOverride, ScheduledOverride, Session, State},
+    wallpaper,
+};
+use crate::utils::calc::parse_duration;
+use chrono::{NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+use std::{error::Error, fs, path::PathBuf, str::FromStr};
+
+// One-shot overrides last an hour unless told otherwise
+const DEFAULT_OVERRIDE_SECONDS: u64 = 3600;
+
+const USAGE: &str = "\
+Usage: expression [COMMAND]
+
+Runs the wallpaper daemon when no command is given.
+
+Commands:
+  collection                Show the active collection
+  collection <name>         Activate a collection (e.g. Nature)
+  collection --clear        Go back to the configured collection
+  random                    Show the active random scope
+  random <scope> [ttl]      Pick from hour, collection or all, optionally for a while (e.g. 2h)
+  random --clear            Go back to the configured random scope
+  session                   Show the focus session
+  session start [rounds] [focus] [break]
+                            Alternate focus and break items (e.g. start 4 25m 5m)
+  session --clear           End the focus session
+  override add <item> [date] <time> [duration]
+                            Show a special item once (e.g. add launch_day 2026-10-20 09:00 1h)
+  override list             Show the pending overrides
+  override cancel <id>      Cancel an override
+  help                      Show this message";
+
+/// Commands for changing a running daemon, e.g. `expression collection Nature`.
+/// They only write the state file, which the daemon picks up within a few seconds.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Help,
+    ShowCollection,
+    SetCollection(String),
+    ClearCollection,
+    ShowRandom,
+    /// Scope and time to live in seconds
+    SetRandom(RandomScope, Option<u64>),
+    ClearRandom,
+    ShowSession,
+    /// Rounds and phase lengths in seconds, the config's when None
+    StartSession {
+        rounds: Option<u32>,
+        focus: Option<u64>,
+        rest: Option<u64>,
+    },
+    ClearSession,
+    /// Special item, start (today or tomorrow without a date) and duration in seconds
+    AddOverride {
+        item: String,
+        date: Option<NaiveDate>,
+        time: NaiveTime,
+        duration: u64,
+    },
+    ListOverrides,
+    CancelOverride(u32),
+}
+
+impl Command {
+    /// Parses the arguments after the program name
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["help" | "--help" | "-h"] => Ok(Command::Help),
+            ["collection"] => Ok(Command::ShowCollection),
+            ["collection", "--clear"] => Ok(Command::ClearCollection),
+            // Collection names may contain spaces
+            ["collection", name @ ..] => Ok(Command::SetCollection(name.join(" "))),
+            ["random"] => Ok(Command::ShowRandom),
+            ["random", "--clear"] => Ok(Command::ClearRandom),
+            ["random", scope] => Ok(Command::SetRandom(RandomScope::parse(scope)?, None)),
+            ["random", scope, ttl] => {
+                let ttl = parse_duration(ttl)
+                    .filter(|seconds| *seconds > 0)
+                    .ok_or_else(|| format!("Invalid duration: {}", ttl))?;
+                Ok(Command::SetRandom(RandomScope::parse(scope)?, Some(ttl)))
+            }
+            ["session"] => Ok(Command::ShowSession),
+            ["session", "--clear"] => Ok(Command::ClearSession),
+            ["session", "start", options @ ..] if options.len() <= 3 => {
+                let rounds = options
+                    .first()
+                    .map(|rounds| {
+                        rounds
+                            .parse()
+                            .ok()
+                            .filter(|rounds| *rounds > 0)
+                            .ok_or_else(|| format!("Invalid rounds: {}", rounds))
+                    })
+                    .transpose()?;
+                let duration = |index: usize, min: u64| {
+                    options
+                        .get(index)
+                        .map(|value| {
+                            parse_duration(value)
+                                .filter(|seconds| *seconds >= min)
+                                .ok_or_else(|| format!("Invalid duration: {}", value))
+                        })
+                        .transpose()
+                };
+                Ok(Command::StartSession {
+                    rounds,
+                    focus: duration(1, 1)?,
+                    rest: duration(2, 0)?,
+                })
+            }
+            ["override", "add", item, when @ ..] => {
+                let (date, rest) = match when {
+                    [date, rest @ ..] if NaiveDate::from_str(date).is_ok() => {
+                        (NaiveDate::from_str(date).ok(), rest)
+                    }
+                    _ => (None, when),
+                };
+                let (time, duration) = match rest {
+                    [time] => (*time, None),
+                    [time, duration] => (*time, Some(*duration)),
+                    _ => return Err(format!("Unknown command: {}\n\n{}", args.join(" "), USAGE)),
+                };
+                let time = NaiveTime::parse_from_str(time, "%H:%M")
+                    .map_err(|_| format!("Invalid time: {}", time))?;
+                let duration = match duration {
+                    Some(duration) => parse_duration(duration)
+                        .filter(|seconds| *seconds > 0)
+                        .ok_or_else(|| format!("Invalid duration: {}", duration))?,
+                    None => DEFAULT_OVERRIDE_SECONDS,
+                };
+                Ok(Command::AddOverride {
+                    item: item.to_string(),
+                    date,
+                    time,
+                    duration,
+                })
+            }
+            ["override", "list"] => Ok(Command::ListOverrides),
+            ["override", "cancel", id] => {
+                let id = id
+                    .trim_start_matches('#')
+                    .parse()
+                    .map_err(|_| format!("Invalid override id: {}", id))?;
+                Ok(Command::CancelOverride(id))
+            }
+            _ => Err(format!("Unknown command: {}\n\n{}", args.join(" "), USAGE)),
+        }
+    }
+}
+
+/// Runs a command against the daemon's state file
+pub fn run(command: Command, config: &Config) -> Result<(), Box<dyn Error>> {
+    if command == Command::Help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let state_path = state::state_path().ok_or("No state directory on this platform")?;
+    // Held until the command is done, so the daemon can't write in between
+    let _lock = state::lock(&state_path)?;
+    let mut state = State::load(&state_path);
+    match command {
+        Command::Help => {}
+        Command::ShowCollection => show_collection(&state, config),
+        Command::SetCollection(name) => {
+            let collections_dir = &config.directories.collections;
+            let collection = wallpaper::find_collection(collections_dir, &[&name])
+                .ok_or_else(|| format!("Collection not found in {}: {}", collections_dir, name))?;
+            let name = collection_name(collection);
+            println!("Active collection: {}", name);
+            state.collection = Some(name);
+            state.save(&state_path)?;
+        }
+        Command::ClearCollection => {
+            state.collection = None;
+            state.save(&state_path)?;
+            show_collection(&state, config);
+        }
+        Command::ShowRandom => {
+            let now = Utc::now().timestamp();
+            match (&state.random, &config.general.random_scope) {
+                (Some(random), _) if random.is_active(now) => {
+                    println!("Random scope: {}", describe_random(random, config))
+                }
+                (_, Some(scope)) => println!("Random scope: {} (config)", scope),
+                _ => println!("No random scope"),
+            }
+        }
+        Command::SetRandom(scope, ttl) => {
+            let until = ttl.map(|ttl| Utc::now().timestamp() + ttl as i64);
+            let random = RandomOverride { scope, until };
+            println!("Random scope: {}", describe_random(&random, config));
+            state.random = Some(random);
+            state.save(&state_path)?;
+        }
+        Command::ShowSession => show_session(&state, config),
+        Command::StartSession {
+            rounds,
+            focus,
+            rest,
+        } => {
+            let defaults = &config.session;
+            let session = Session {
+                start: Utc::now().timestamp(),
+                rounds: rounds.unwrap_or(defaults.rounds),
+                focus: focus.or(defaults.focus_seconds()).unwrap_or_default(),
+                rest: rest.or(defaults.break_seconds()).unwrap_or_default(),
+                focus_item: defaults.focus_item.clone(),
+                break_item: defaults.break_item.clone(),
+            };
+            state.session = Some(session);
+            state.save(&state_path)?;
+            show_session(&state, config);
+        }
+        Command::ClearSession => {
+            state.session = None;
+            state.save(&state_path)?;
+            println!("No session");
+        }
+        Command::AddOverride {
+            item,
+            date,
+            time,
+            duration,
+        } => {
+            let now = Utc::now().with_timezone(&config.zone);
+            // Without a date, the next time it's that time
+            let date = date.unwrap_or_else(|| match now.time() < time {
+                true => now.date_naive(),
+                false => now.date_naive() + TimeDelta::days(1),
+            });
+            let start = config
+                .zone
+                .from_local_datetime(&date.and_time(time))
+                .earliest()
+                .ok_or_else(|| format!("No such time in {}: {} {}", config.zone, date, time))?;
+            let end = start.timestamp() + duration as i64;
+            if end <= now.timestamp() {
+                return Err(format!("Override would already be over: {} {}", date, time).into());
+            }
+            if !has_special_item(&config.directories.special, &item) {
+                println!(
+                    "Note: no item `{}` in {} yet",
+                    item, config.directories.special
+                );
+            }
+            state.expire_overrides(now.timestamp());
+            let id = state.add_override(item, start.timestamp(), end);
+            state.save(&state_path)?;
+            if let Some(entry) = state.overrides.iter().find(|entry| entry.id == id) {
+                println!("Override {}", describe_override(entry, config));
+            }
+        }
+        Command::ListOverrides => {
+            let now = Utc::now().timestamp();
+            if !state.expire_overrides(now).is_empty() {
+                state.save(&state_path)?;
+            }
+            if state.overrides.is_empty() {
+                println!("No overrides");
+            }
+            for entry in &state.overrides {
+                match entry.is_active(now) {
+                    true => println!("{} (active)", describe_override(entry, config)),
+                    false => println!("{}", describe_override(entry, config)),
+                }
+            }
+        }
+        Command::CancelOverride(id) => {
+            let index = state
+                .overrides
+                .iter()
+                .position(|entry| entry.id == id)
+                .ok_or_else(|| format!("No override #{}", id))?;
+            let entry = state.overrides.remove(index);
+            state.save(&state_path)?;
+            println!("Cancelled {}", describe_override(&entry, config));
+        }
+        Command::ClearRandom => {
+            state.random = None;
+            state.save(&state_path)?;
+            match &config.general.random_scope {
+                Some(scope) => println!("Random scope: {} (config)", scope),
+                None => println!("No random scope"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Active collection and where it comes from, plus the next scheduled switch
+fn show_collection(state: &State, config: &Config) {
+    let today = Utc::now().with_timezone(&config.zone).date_naive();
+    let schedule = &config.collection_schedule;
+    match (
+        &state.collection,
+        rotation::scheduled_collection(schedule, today),
+        &config.general.collection,
+    ) {
+        (Some(collection), _, _) => println!("Active collection: {} (runtime)", collection),
+        (None, Some(collection), _) => println!("Active collection: {} (schedule)", collection),
+        (None, None, Some(collection)) => println!("Active collection: {} (config)", collection),
+        (None, None, None) => println!("No active collection"),
+    }
+    if let Some((date, next)) = rotation::next_switch(schedule, today) {
+        let next = next.or(config.general.collection.as_deref());
+        println!(
+            "Next scheduled switch: {} to {}",
+            date.format("%a %Y-%m-%d"),
+            next.unwrap_or("no collection")
+        );
+    }
+}
+
+/// Current phase of the session, e.g. `focus 2/4 until 10:25`
+fn show_session(state: &State, config: &Config) {
+    let now = Utc::now().timestamp();
+    let time = |timestamp: i64| {
+        config
+            .zone
+            .timestamp_opt(timestamp, 0)
+            .single()
+            .map_or_else(String::new, |time| time.format("%H:%M").to_string())
+    };
+    match state
+        .session
+        .as_ref()
+        .and_then(|session| Some((session, session.phase(now)?)))
+    {
+        Some((session, (phase, round, _, end))) => println!(
+            "Session: {} {}/{} until {} (`{}`, session ends {})",
+            phase,
+            round,
+            session.rounds,
+            time(end),
+            session.item(phase),
+            time(session.end())
+        ),
+        None => println!("No session"),
+    }
+}
+
+/// e.g. `#3 launch_day, Tue 2026-10-20 09:00-10:00`
+fn describe_override(entry: &ScheduledOverride, config: &Config) -> String {
+    let time = |timestamp: i64| config.zone.timestamp_opt(timestamp, 0).single();
+    match (time(entry.start), time(entry.end)) {
+        (Some(start), Some(end)) if start.date_naive() == end.date_naive() => format!(
+            "#{} {}, {} {}-{}",
+            entry.id,
+            entry.item,
+            start.date_naive().format("%a %Y-%m-%d"),
+            start.time().format("%H:%M"),
+            end.time().format("%H:%M")
+        ),
+        (Some(start), Some(end)) => format!(
+            "#{} {}, {} until {}",
+            entry.id,
+            entry.item,
+            start.naive_local().format("%a %Y-%m-%d %H:%M"),
+            end.naive_local().format("%a %Y-%m-%d %H:%M")
+        ),
+        _ => format!("#{} {}", entry.id, entry.item),
+    }
+}
+
+/// Whether the special collection has an entry or group named `item`
+fn has_special_item(special_dir: &str, item: &str) -> bool {
+    fs::read_dir(special_dir).is_ok_and(|entries| {
+        entries.flatten().any(|entry| {
+            let path = entry.path();
+            let name = match path.is_dir() {
+                true => path.file_name(),
+                false => path.file_stem(),
+            };
+            name.is_some_and(|name| name == item)
+        })
+    })
+}
+
+/// e.g. `collection until 16:30`
+fn describe_random(random: &RandomOverride, config: &Config) -> String {
+    match random
+        .until
+        .and_then(|until| config.zone.timestamp_opt(until, 0).single())
+    {
+        Some(until) => format!("{} until {}", random.scope, until.format("%H:%M")),
+        None => random.scope.to_string(),
+    }
+}
+
+fn collection_name(collection: PathBuf) -> String {
+    collection
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -1,5 +1,20 @@
-use serde::Deserialize;
+use crate::core::{
+    calendar::Event,
+    rules::{Condition, Rule},
+    schedule::{self, SpecialEntry},
+};
+use crate::utils::calc::parse_duration;
+use crate::utils::{
+    appearance::ColorScheme,
+    astro::AstroCondition,
+    season::{Hemisphere, SeasonBoundaries},
+    solar::Location,
+    zone::Zone,
+};
+use chrono::Month;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 use toml;
@@ -9,13 +24,209 @@ use toml;
 pub enum GroupSelectionStrategy {
     Spread,
     Random,
+    /// Random order without repeats until the whole group was shown
+    Shuffle,
+    /// Random, favouring wallpapers with a higher weight
+    Weighted,
+    /// Next wallpaper in sorted order each time the group is activated
+    Sequential,
+    /// Same wallpaper for the whole day, seeded from the date and group
+    Daily,
+}
+
+/// Pool a randomized scope picks from, instead of the scheduled item
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RandomScope {
+    /// Any wallpaper of the current hour's active item (root with collection overrides)
+    Hour,
+    /// Any wallpaper of any hour, from the active items (root with collection overrides)
+    Collection,
+    /// Any wallpaper of any hour, from the root and every collection
+    All,
+}
+
+impl RandomScope {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "hour" => Ok(RandomScope::Hour),
+            "collection" => Ok(RandomScope::Collection),
+            "all" => Ok(RandomScope::All),
+            _ => Err(format!(
+                "Unknown random scope `{}` (hour, collection, all)",
+                name
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RandomScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RandomScope::Hour => write!(f, "hour"),
+            RandomScope::Collection => write!(f, "collection"),
+            RandomScope::All => write!(f, "all"),
+        }
+    }
+}
+
+/// Where a wallpaper can come from. In `layers` order, the first layer with an item wins.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layer {
+    /// A focus session started with `expression session start`
+    Session,
+    /// One-shot overrides added with `expression override add`
+    Override,
+    /// `[[rules]]`, the first matching rule
+    Rules,
+    /// Special entries with a date, e.g. `12-25` or `12-25 09`
+    Dated,
+    /// Events of the `[calendar]` file
+    Calendar,
+    /// Special entries by time only, e.g. `23`, `sunset` or a cron schedule
+    Hourly,
+    /// Solar items in the wallpaper dir, e.g. `sunset.jpg`
+    Solar,
+    /// Collection overrides for the hour (season, astronomy, color scheme, active collection)
+    Collection,
+    /// Root items (or the timeline) for the hour
+    Root,
+    /// Gap policy for hours without an item
+    Gap,
+}
+
+pub const DEFAULT_LAYERS: [Layer; 10] = [
+    Layer::Session,
+    Layer::Override,
+    Layer::Rules,
+    Layer::Dated,
+    Layer::Calendar,
+    Layer::Hourly,
+    Layer::Solar,
+    Layer::Collection,
+    Layer::Root,
+    Layer::Gap,
+];
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Layer::Session => "session",
+            Layer::Override => "override",
+            Layer::Rules => "rules",
+            Layer::Dated => "dated",
+            Layer::Calendar => "calendar",
+            Layer::Hourly => "hourly",
+            Layer::Solar => "solar",
+            Layer::Collection => "collection",
+            Layer::Root => "root",
+            Layer::Gap => "gap",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What to show during hours without an item
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GapPolicy {
+    /// Carry the last earlier item forward (wraps across midnight)
+    Hold,
+    /// Show the next upcoming item early
+    Next,
+    /// Show the closest item, earlier one on ties
+    Nearest,
+    /// Show the item of a random hour
+    Random,
+    /// Keep whatever is currently shown
+    #[default]
+    None,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub general: GeneralConfig,
     pub directories: DirectoryConfig,
-    pub special_entries: BTreeMap<String, String>,
+    pub special_entries: BTreeMap<String, SpecialEntryConfig>,
+
+    /// Required for solar keyed items and entries (sunrise, sunset, etc)
+    pub location: Option<Location>,
+
+    #[serde(default)]
+    pub seasons: SeasonConfig,
+
+    #[serde(default)]
+    pub timeline: TimelineConfig,
+
+    #[serde(default)]
+    pub crossfade: CrossfadeConfig,
+
+    #[serde(default)]
+    pub collection_schedule: CollectionScheduleConfig,
+
+    #[serde(default)]
+    pub appearance: AppearanceConfig,
+
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+
+    #[serde(default)]
+    pub session: SessionConfig,
+
+    /// Collections activated by astronomical conditions, e.g. `"moon:full" = "full_moon"`
+    #[serde(default)]
+    pub astronomy_collections: BTreeMap<String, String>,
+
+    /// Conditional overrides, first matching rule wins over special entries
+    #[serde(default)]
+    pub rules: Vec<RuleConfig>,
+
+    /// Commands usable in rule conditions as `probe.NAME`, hold if they exit with 0
+    #[serde(default)]
+    pub probes: BTreeMap<String, String>,
+
+    /// Parsed `rules`, populated on load
+    #[serde(skip)]
+    pub rule_set: Vec<Rule>,
+
+    /// Parsed `special_entries`, populated on load
+    #[serde(skip)]
+    pub special_schedule: Vec<SpecialEntry>,
+
+    /// Parsed `astronomy_collections`, populated on load
+    #[serde(skip)]
+    pub astronomy_schedule: Vec<(AstroCondition, String)>,
+
+    /// Parsed `general.timezone`, populated on load
+    #[serde(skip)]
+    pub zone: Zone,
+}
+
+/// A `[[rules]]` entry, e.g. `when = "battery < 20 && !charging"` and `item = "low_power"`
+#[derive(Debug, Deserialize)]
+pub struct RuleConfig {
+    /// Shown in the log, defaults to the item
+    pub name: Option<String>,
+    pub when: String,
+    /// Item in the special collection
+    pub item: String,
+}
+
+/// Value of a `[special_entries]` line.
+/// Either the item name (`23 = "sleep_time"`)
+/// or a cron schedule (`standup = { cron = "0 9 * * 1-5", duration = "15m" }`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SpecialEntryConfig {
+    Item(String),
+    Cron {
+        cron: String,
+        /// Defaults to the entry key
+        item: Option<String>,
+        /// Defaults to an hour
+        duration: Option<String>,
+    },
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,9 +234,256 @@ pub struct GeneralConfig {
     pub backend: String,
     pub enable_special: bool,
     pub group_selection_strategy: GroupSelectionStrategy,
+    #[serde(default)]
+    pub gap_policy: GapPolicy,
+    /// Extra seed for the `daily` strategy, change it for a different daily pick
+    #[serde(default)]
+    pub daily_seed: String,
+    /// IANA time zone to schedule in (e.g. `Asia/Tokyo`), defaults to the system zone
+    pub timezone: Option<String>,
+    /// Collection overriding the root items hour by hour (e.g. `Nature`)
+    pub collection: Option<String>,
+    /// Pick randomly from a wider pool than the scheduled item
+    pub random_scope: Option<RandomScope>,
+    /// Priority of wallpaper sources, layers left out are skipped
+    #[serde(default = "default_layers")]
+    pub layers: Vec<Layer>,
     pub execute_on_change: Option<String>,
 }
 
+/// Activates `spring/`, `summer/`, `autumn/` or `winter/` collection
+#[derive(Debug, Default, Deserialize)]
+pub struct SeasonConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub boundaries: SeasonBoundaries,
+    #[serde(default)]
+    pub hemisphere: Hemisphere,
+}
+
+/// Lays a plain folder of images across the day instead of `HH` names
+#[derive(Debug, Default, Deserialize)]
+pub struct TimelineConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Explicit order of file names, natural order otherwise
+    #[serde(default)]
+    pub order: Vec<String>,
+    /// First hour of the window
+    #[serde(default)]
+    pub start: u8,
+    /// Hour the window ends at (exclusive), wraps past midnight if before start.
+    /// Defaults to a whole day from start.
+    pub end: Option<u8>,
+    /// Start hour of each image's slot, even slots otherwise
+    #[serde(default)]
+    pub slots: Vec<u8>,
+}
+
+impl TimelineConfig {
+    /// Number of hours covered by the window
+    pub fn window_hours(&self) -> u8 {
+        match self.end {
+            Some(end) if end % 24 != self.start => (end + 24 - self.start) % 24,
+            _ => 24,
+        }
+    }
+
+    /// Hours from the window start to the given hour
+    pub fn hour_offset(&self, hour: u8) -> u8 {
+        (hour + 24 - self.start) % 24
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.start >= 24 || self.end.is_some_and(|end| end > 24) {
+            return Err("window hours must be within 0-24".into());
+        }
+        let mut previous = None;
+        for slot in &self.slots {
+            // Range check first, `hour_offset` overflows past 24
+            if *slot >= 24 || self.hour_offset(*slot) >= self.window_hours() {
+                return Err(format!("slot {} is outside the window", slot));
+            }
+            let offset = self.hour_offset(*slot);
+            if previous.is_some_and(|previous| offset <= previous) {
+                return Err(format!("slot {} is not after the previous slot", slot));
+            }
+            previous = Some(offset);
+        }
+        Ok(())
+    }
+}
+
+/// Activates collections by date, rotating through a list or by month
+#[derive(Debug, Default, Deserialize)]
+pub struct CollectionScheduleConfig {
+    /// Rotation period through `collections`
+    pub rotate: Option<RotationPeriod>,
+    #[serde(default)]
+    pub collections: Vec<String>,
+    /// Collection by month, e.g. `12 = "holidays"` or `december = "holidays"`, wins over rotation
+    #[serde(default)]
+    pub months: BTreeMap<String, String>,
+
+    /// Parsed `months` (1-12), populated on load
+    #[serde(skip)]
+    pub month_collections: BTreeMap<u32, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RotationPeriod {
+    Daily,
+    /// Switches on Mondays
+    Weekly,
+    /// Switches on the 1st
+    Monthly,
+}
+
+impl CollectionScheduleConfig {
+    fn parse_months(&mut self) -> Result<(), String> {
+        if self.rotate.is_some() && self.collections.is_empty() {
+            return Err("rotate needs at least one collection".into());
+        }
+        for (key, collection) in &self.months {
+            let month = match key.parse::<u32>() {
+                Ok(month @ 1..=12) => month,
+                Ok(_) => return Err(format!("month {} is outside 1-12", key)),
+                Err(_) => key
+                    .parse::<Month>()
+                    .map_err(|_| format!("unknown month `{}`", key))?
+                    .number_from_month(),
+            };
+            self.month_collections.insert(month, collection.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Follows the desktop light/dark preference
+#[derive(Debug, Default, Deserialize)]
+pub struct AppearanceConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub source: SchemeSource,
+    /// File containing `dark` or `light`, read by the `file` and `auto` sources
+    pub file: Option<String>,
+    /// Collection shown in dark mode (default: `dark`)
+    pub dark: Option<String>,
+    /// Collection shown in light mode (default: `light`)
+    pub light: Option<String>,
+}
+
+/// Special items triggered by the events of a local iCalendar file
+#[derive(Debug, Default, Deserialize)]
+pub struct CalendarConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// `.ics` file, e.g. a team calendar export. Re-read whenever it changes.
+    pub file: Option<String>,
+    /// Event summaries or categories to special items, e.g. `"Release" = "launch_day"`.
+    /// Events match an item of the same name otherwise.
+    #[serde(default)]
+    pub events: BTreeMap<String, String>,
+}
+
+impl CalendarConfig {
+    /// Special items an event may show: items mapped from its names first, then the names
+    pub fn items<'a>(&'a self, event: &'a Event) -> Vec<&'a str> {
+        let mapped = event.names().filter_map(|name| {
+            self.events
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, item)| item.as_str())
+        });
+        mapped.chain(event.names()).collect()
+    }
+}
+
+/// Where the color scheme is read from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemeSource {
+    /// The file if set, otherwise the portal, otherwise gsettings
+    #[default]
+    Auto,
+    /// `org.freedesktop.appearance color-scheme` from the XDG desktop portal
+    Portal,
+    /// GNOME's `org.gnome.desktop.interface color-scheme`
+    Gsettings,
+    File,
+}
+
+impl AppearanceConfig {
+    /// Collection tied to a color scheme
+    pub fn collection(&self, scheme: ColorScheme) -> &str {
+        let collection = match scheme {
+            ColorScheme::Dark => &self.dark,
+            ColorScheme::Light => &self.light,
+        };
+        collection.as_deref().unwrap_or(scheme.name())
+    }
+}
+
+/// Defaults of focus sessions (`expression session start`)
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Length of a focus phase, e.g. `25m`
+    pub focus: String,
+    /// Length of a break, e.g. `5m`
+    #[serde(rename = "break")]
+    pub rest: String,
+    /// Focus phases (each followed by a break)
+    pub rounds: u32,
+    /// Item in the special collection shown while focusing
+    pub focus_item: String,
+    /// Item in the special collection shown during breaks
+    pub break_item: String,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig {
+            focus: "25m".to_string(),
+            rest: "5m".to_string(),
+            rounds: 4,
+            focus_item: "focus".to_string(),
+            break_item: "break".to_string(),
+        }
+    }
+}
+
+impl SessionConfig {
+    pub fn focus_seconds(&self) -> Option<u64> {
+        parse_duration(&self.focus).filter(|seconds| *seconds > 0)
+    }
+
+    pub fn break_seconds(&self) -> Option<u64> {
+        parse_duration(&self.rest)
+    }
+}
+
+/// Blends the current hour's wallpaper into the next one in steps
+#[derive(Debug, Default, Deserialize)]
+pub struct CrossfadeConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Time between frames, e.g. `5m` (default)
+    pub step: Option<String>,
+}
+
+impl CrossfadeConfig {
+    pub fn step_seconds(&self) -> Option<u64> {
+        match &self.step {
+            Some(step) => parse_duration(step).filter(|seconds| *seconds > 0),
+            None => Some(300),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DirectoryConfig {
     pub wallpaper: String,
@@ -74,11 +532,104 @@ impl Config {
         config.directories.wallpaper = expand_path(&config.directories.wallpaper);
         config.directories.special = expand_path(&config.directories.special);
         config.directories.collections = expand_path(&config.directories.collections);
+        config.appearance.file = config.appearance.file.as_deref().map(expand_path);
+        config.calendar.file = config.calendar.file.as_deref().map(expand_path);
+
+        if let Some(timezone) = &config.general.timezone {
+            config.zone = Zone::parse(timezone)?;
+        }
+
+        config.special_schedule = schedule::parse_special_entries(&config.special_entries)?;
+        if config.location.is_none()
+            && !schedule::solar_anchors(&config.special_schedule).is_empty()
+        {
+            return Err(
+                "Solar special entries require latitude and longitude in [location]".into(),
+            );
+        }
+
+        if config.timeline.enabled {
+            config
+                .timeline
+                .validate()
+                .map_err(|err| format!("Invalid [timeline]: {}", err))?;
+        }
+
+        if config.crossfade.enabled && config.crossfade.step_seconds().is_none() {
+            return Err(format!(
+                "Invalid [crossfade] step: {}",
+                config.crossfade.step.as_deref().unwrap_or_default()
+            )
+            .into());
+        }
+
+        for (index, layer) in config.general.layers.iter().enumerate() {
+            if config.general.layers[..index].contains(layer) {
+                return Err(format!("Invalid layers: `{}` is listed twice", layer).into());
+            }
+        }
+
+        for rule in &config.rules {
+            let name = rule.name.clone().unwrap_or_else(|| rule.item.clone());
+            let condition = Condition::parse(&rule.when)
+                .map_err(|err| format!("Invalid rule `{}`: {}", name, err))?;
+            if let Some(probe) = condition
+                .probes()
+                .into_iter()
+                .find(|probe| !config.probes.contains_key(*probe))
+            {
+                return Err(format!("Invalid rule `{}`: unknown probe `{}`", name, probe).into());
+            }
+            config.rule_set.push(Rule {
+                name,
+                condition,
+                item: rule.item.clone(),
+            });
+        }
+
+        if config.appearance.enabled
+            && config.appearance.source == SchemeSource::File
+            && config.appearance.file.is_none()
+        {
+            return Err("Invalid [appearance]: the file source needs a file".into());
+        }
+
+        if config.session.focus_seconds().is_none() {
+            return Err(format!("Invalid [session] focus: {}", config.session.focus).into());
+        }
+        if config.session.break_seconds().is_none() {
+            return Err(format!("Invalid [session] break: {}", config.session.rest).into());
+        }
+        if config.session.rounds == 0 {
+            return Err("Invalid [session]: rounds must be at least 1".into());
+        }
+
+        if config.calendar.enabled && config.calendar.file.is_none() {
+            return Err("Invalid [calendar]: a file is needed".into());
+        }
+
+        config
+            .collection_schedule
+            .parse_months()
+            .map_err(|err| format!("Invalid [collection_schedule]: {}", err))?;
+
+        for (key, collection) in &config.astronomy_collections {
+            let condition = AstroCondition::parse(key)
+                .unwrap_or_else(|| Err(format!("unknown condition `{}`", key)))
+                .map_err(|err| format!("Invalid astronomy collection `{}`: {}", key, err))?;
+            config
+                .astronomy_schedule
+                .push((condition, collection.clone()));
+        }
 
         Ok(config)
     }
 }
 
+fn default_layers() -> Vec<Layer> {
+    DEFAULT_LAYERS.to_vec()
+}
+
 fn merge_toml(mut base: toml::Value, overrides: toml::Value) -> toml::Value {
     if let (toml::Value::Table(base_table), toml::Value::Table(override_table)) =
         (&mut base, overrides)
@@ -124,11 +675,17 @@ use log2::error;
 #[derive(Debug, Deserialize, Clone)]
 pub struct GroupConfig {
     pub general: Option<GeneralGroupConfig>,
+
+    /// Weights by file name for the `weighted` strategy (default: 1)
+    #[serde(default)]
+    pub weights: BTreeMap<String, u32>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct GeneralGroupConfig {
     pub selection_strategy: Option<GroupSelectionStrategy>,
+    /// Picks a new wallpaper every N minutes while the group is active
+    pub interval_minutes: Option<u32>,
 }
 
 /// Selects config from the first Group in the list of WallpaperItem.
@@ -0,0 +1,781 @@
+use chrono::{
+    DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone,
+    Utc, Weekday,
+};
+use log2::warn;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// Changes further ahead are found on a later wake up, which happens at least hourly
+const LOOKAHEAD_HOURS: i64 = 24;
+
+/// An event of an iCalendar (`.ics`) file, possibly recurring
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub summary: String,
+    pub categories: Vec<String>,
+    /// Start of the first occurrence, in `frame`
+    start: NaiveDateTime,
+    frame: Frame,
+    duration: TimeDelta,
+    recurrence: Option<Recurrence>,
+    /// Occurrences left out, in `frame`
+    exdates: Vec<NaiveDateTime>,
+    uid: Option<String>,
+    /// Set on a changed instance of a recurring event, which replaces that occurrence
+    recurrence_id: Option<NaiveDateTime>,
+}
+
+/// What an event's local times are relative to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frame {
+    /// Wall clock time of the schedule's zone, also used for all day events
+    Floating,
+    Utc,
+    Zone(chrono_tz::Tz),
+}
+
+/// An `RRULE`, e.g. `FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10`
+#[derive(Debug, Clone)]
+struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    /// Last possible start (inclusive), in the event's frame
+    until: Option<NaiveDateTime>,
+    /// Weekdays with an optional ordinal, e.g. `-1FR` is the last Friday
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A property line, e.g. `DTSTART;TZID=Europe/Berlin:20250101T090000`
+struct Property<'a> {
+    name: String,
+    params: Vec<(String, &'a str)>,
+    value: &'a str,
+}
+
+impl Property<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim_matches('"'))
+    }
+}
+
+/// Parses the events of an iCalendar file.
+/// Events which can't be understood (e.g. an hourly recurrence) are skipped with a warning.
+///
+/// # Example
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use expression::core::calendar::{active_events, parse_calendar};
+///
+/// let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Release\r\n\
+///            DTSTART:20251020T090000Z\r\nDTEND:20251020T100000Z\r\nEND:VEVENT\r\nEND:VCALENDAR";
+/// let events = parse_calendar(ics);
+///
+/// let now = Utc.with_ymd_and_hms(2025, 10, 20, 9, 30, 0).unwrap();
+/// assert_eq!(active_events(&events, now)[0].0.summary, "Release");
+/// ```
+pub fn parse_calendar(content: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    // Depth of components nested in an event (e.g. VALARM), their properties are ignored
+    let mut nested = 0;
+
+    let lines = unfold(content);
+    for line in &lines {
+        let Some(property) = parse_property(line) else {
+            continue;
+        };
+        match (
+            property.name.as_str(),
+            property.value.to_uppercase().as_str(),
+        ) {
+            ("BEGIN", "VEVENT") if current.is_none() => current = Some(Vec::new()),
+            ("END", "VEVENT") if nested == 0 => {
+                let Some(properties) = current.take() else {
+                    continue;
+                };
+                match parse_event(&properties) {
+                    Ok(Some(event)) => events.push(event),
+                    Ok(None) => {}
+                    Err(err) => warn!("Skipping calendar event: {}", err),
+                }
+            }
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", _) if current.is_some() => nested -= 1,
+            _ => {
+                if let (Some(properties), 0) = (&mut current, nested) {
+                    properties.push(property);
+                }
+            }
+        }
+    }
+
+    // Changed instances replace their occurrence of the recurring event
+    let changed: Vec<(String, NaiveDateTime)> = events
+        .iter()
+        .filter_map(|event| Some((event.uid.clone()?, event.recurrence_id?)))
+        .collect();
+    for event in events
+        .iter_mut()
+        .filter(|event| event.recurrence_id.is_none())
+    {
+        for (uid, recurrence_id) in &changed {
+            if event.uid.as_ref() == Some(uid) {
+                event.exdates.push(*recurrence_id);
+            }
+        }
+    }
+    events
+}
+
+/// Occurrences happening at `now`, the latest started first, with their start and end
+pub fn active_events<Tz: TimeZone>(
+    events: &[Event],
+    now: DateTime<Tz>,
+) -> Vec<(&Event, DateTime<Tz>, DateTime<Tz>)> {
+    let until = now.clone() + TimeDelta::seconds(1);
+    let mut active: Vec<_> = events
+        .iter()
+        .flat_map(|event| {
+            event
+                .occurrences(now.clone(), until.clone())
+                .into_iter()
+                .map(move |(start, end)| (event, start, end))
+        })
+        .collect();
+    active.sort_by(|a, b| b.1.cmp(&a.1));
+    active
+}
+
+/// Next time an occurrence starts or ends after `now`, within a day
+pub fn next_change<Tz: TimeZone>(events: &[Event], now: DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let until = now.clone() + TimeDelta::hours(LOOKAHEAD_HOURS);
+    events
+        .iter()
+        .flat_map(|event| event.occurrences(now.clone(), until.clone()))
+        .flat_map(|(start, end)| [start, end])
+        .filter(|time| *time > now)
+        .min()
+}
+
+impl Event {
+    /// Summary and categories, e.g. to look up special items
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.summary.as_str())
+            .chain(self.categories.iter().map(String::as_str))
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Occurrences overlapping `from` until `to`, in the zone of `from`
+    pub fn occurrences<Tz: TimeZone>(
+        &self,
+        from: DateTime<Tz>,
+        to: DateTime<Tz>,
+    ) -> Vec<(DateTime<Tz>, DateTime<Tz>)> {
+        let zone = from.timezone();
+        // A day of slack covers any offset between the frame and the zone
+        let limit = self.frame.naive(&to) + TimeDelta::days(1);
+        self.starts(limit)
+            .into_iter()
+            .filter_map(|start| {
+                let end = start + self.duration;
+                Some((
+                    self.frame.localize(start, &zone)?,
+                    self.frame.localize(end, &zone)?,
+                ))
+            })
+            .filter(|(start, end)| *start < to && *end > from)
+            .collect()
+    }
+
+    /// Starts of all occurrences up to `limit`, in the event's frame
+    fn starts(&self, limit: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let mut starts = match &self.recurrence {
+            None => vec![self.start]
+                .into_iter()
+                .filter(|start| *start <= limit)
+                .collect(),
+            Some(recurrence) => recurrence.starts(self.start, limit),
+        };
+        starts.retain(|start| !self.exdates.contains(start));
+        starts
+    }
+}
+
+impl Frame {
+    /// Local time of `time` in this frame
+    fn naive<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> NaiveDateTime {
+        match self {
+            Frame::Floating => time.naive_local(),
+            Frame::Utc => time.naive_utc(),
+            Frame::Zone(tz) => time.with_timezone(tz).naive_local(),
+        }
+    }
+
+    /// Time in `zone` of a local time in this frame
+    fn localize<Tz: TimeZone>(&self, time: NaiveDateTime, zone: &Tz) -> Option<DateTime<Tz>> {
+        match self {
+            Frame::Floating => zone.from_local_datetime(&time).earliest(),
+            Frame::Utc => Some(zone.from_utc_datetime(&time)),
+            Frame::Zone(tz) => tz
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|time| time.with_timezone(zone)),
+        }
+    }
+
+    /// Converts a local time of another frame into this one
+    fn convert(&self, time: NaiveDateTime, from: Frame) -> NaiveDateTime {
+        match (from, self) {
+            (Frame::Floating, _) | (_, Frame::Floating) => time,
+            (from, to) => from.localize(time, &Utc).map_or(time, |utc| to.naive(&utc)),
+        }
+    }
+}
+
+impl Recurrence {
+    fn parse(value: &str, frame: Frame) -> Result<Self, String> {
+        let mut recurrence = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+        };
+        let mut frequency = None;
+        for part in value.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("invalid RRULE part `{}`", part))?;
+            let invalid = || format!("invalid RRULE {} `{}`", key, value);
+            match key.to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("unsupported RRULE frequency `{}`", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    recurrence.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(invalid)?
+                }
+                "COUNT" => recurrence.count = Some(value.parse().map_err(|_| invalid())?),
+                "UNTIL" => {
+                    let (until, until_frame) = parse_time(value, None).ok_or_else(invalid)?;
+                    // A date includes its whole day
+                    let until = match value.len() {
+                        8 => until + TimeDelta::days(1) - TimeDelta::seconds(1),
+                        _ => until,
+                    };
+                    recurrence.until = Some(frame.convert(until, until_frame));
+                }
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        recurrence
+                            .by_day
+                            .push(parse_by_day(day).ok_or_else(invalid)?);
+                    }
+                }
+                "BYMONTHDAY" => {
+                    for day in value.split(',') {
+                        let day: i32 = day.parse().map_err(|_| invalid())?;
+                        if day == 0 || day.abs() > 31 {
+                            return Err(invalid());
+                        }
+                        recurrence.by_month_day.push(day);
+                    }
+                }
+                "BYMONTH" => {
+                    for month in value.split(',') {
+                        let month = month
+                            .parse()
+                            .ok()
+                            .filter(|month| (1..=12).contains(month))
+                            .ok_or_else(invalid)?;
+                        recurrence.by_month.push(month);
+                    }
+                }
+                "WKST" => {}
+                _ => return Err(format!("unsupported RRULE part `{}`", key)),
+            }
+        }
+        recurrence.frequency = frequency.ok_or("RRULE without FREQ")?;
+        Ok(recurrence)
+    }
+
+    /// Starts from `start` up to `limit`, honoring COUNT and UNTIL
+    fn starts(&self, start: NaiveDateTime, limit: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let limit = self.until.map_or(limit, |until| until.min(limit));
+        let mut starts = Vec::new();
+        for index in 0.. {
+            let Some(period) = self.period_start(start.date(), index) else {
+                break;
+            };
+            if period > limit.date() {
+                break;
+            }
+            let mut days = self.period_days(start.date(), period);
+            days.sort();
+            days.dedup();
+            for day in days {
+                let time = day.and_time(start.time());
+                if time < start {
+                    continue;
+                }
+                if time > limit || self.count.is_some_and(|count| starts.len() >= count) {
+                    return starts;
+                }
+                starts.push(time);
+            }
+        }
+        starts
+    }
+
+    /// First day of the `index`th period
+    fn period_start(&self, start: NaiveDate, index: u32) -> Option<NaiveDate> {
+        let steps = index.checked_mul(self.interval)?;
+        match self.frequency {
+            Frequency::Daily => start.checked_add_days(Days::new(steps as u64)),
+            Frequency::Weekly => start
+                .checked_sub_days(Days::new(start.weekday().num_days_from_monday() as u64))?
+                .checked_add_days(Days::new(steps as u64 * 7)),
+            Frequency::Monthly => start.with_day(1)?.checked_add_months(Months::new(steps)),
+            Frequency::Yearly => {
+                NaiveDate::from_ymd_opt(start.year().checked_add(steps as i32)?, 1, 1)
+            }
+        }
+    }
+
+    /// Candidate days of the period starting at `period`
+    fn period_days(&self, start: NaiveDate, period: NaiveDate) -> Vec<NaiveDate> {
+        match self.frequency {
+            Frequency::Daily => vec![period]
+                .into_iter()
+                .filter(|day| self.is_in_month(*day) && self.is_on_month_day(*day))
+                .filter(|day| self.is_on_weekday(*day))
+                .collect(),
+            Frequency::Weekly => {
+                let weekdays: Vec<Weekday> = match self.by_day.is_empty() {
+                    true => vec![start.weekday()],
+                    false => self.by_day.iter().map(|(_, weekday)| *weekday).collect(),
+                };
+                weekdays
+                    .into_iter()
+                    .filter_map(|weekday| {
+                        period.checked_add_days(Days::new(weekday.num_days_from_monday() as u64))
+                    })
+                    .filter(|day| self.is_in_month(*day))
+                    .collect()
+            }
+            Frequency::Monthly if self.is_in_month(period) => self.month_days(start, period),
+            Frequency::Monthly => Vec::new(),
+            Frequency::Yearly if self.by_month.is_empty() && !self.by_day.is_empty() => {
+                // e.g. `BYDAY=20MO`, counted over the whole year
+                let end = NaiveDate::from_ymd_opt(period.year(), 12, 31).unwrap_or(period);
+                self.weekdays_between(period, end)
+            }
+            Frequency::Yearly => {
+                let months = match self.by_month.is_empty() {
+                    true => vec![start.month()],
+                    false => self.by_month.clone(),
+                };
+                months
+                    .into_iter()
+                    .filter_map(|month| NaiveDate::from_ymd_opt(period.year(), month, 1))
+                    .flat_map(|month| self.month_days(start, month))
+                    .collect()
+            }
+        }
+    }
+
+    /// Candidate days of the month starting at `month`
+    fn month_days(&self, start: NaiveDate, month: NaiveDate) -> Vec<NaiveDate> {
+        let last = month
+            .checked_add_months(Months::new(1))
+            .and_then(|next| next.pred_opt())
+            .unwrap_or(month);
+        if !self.by_month_day.is_empty() {
+            return self
+                .by_month_day
+                .iter()
+                .filter_map(|day| match *day > 0 {
+                    true => month.with_day(*day as u32),
+                    false => last.checked_sub_days(Days::new((-day - 1) as u64)),
+                })
+                .filter(|day| day.month() == month.month() && self.is_on_weekday(*day))
+                .collect();
+        }
+        if !self.by_day.is_empty() {
+            return self.weekdays_between(month, last);
+        }
+        month.with_day(start.day()).into_iter().collect()
+    }
+
+    /// Days from `first` to `last` matching BYDAY, ordinals count within that range
+    fn weekdays_between(&self, first: NaiveDate, last: NaiveDate) -> Vec<NaiveDate> {
+        let days: Vec<NaiveDate> = first.iter_days().take_while(|day| *day <= last).collect();
+        let mut matches = Vec::new();
+        for (ordinal, weekday) in &self.by_day {
+            let candidates: Vec<NaiveDate> = days
+                .iter()
+                .copied()
+                .filter(|day| day.weekday() == *weekday)
+                .collect();
+            match ordinal {
+                None => matches.extend(candidates),
+                Some(ordinal) => {
+                    let index = match *ordinal > 0 {
+                        true => ordinal - 1,
+                        false => candidates.len() as i32 + ordinal,
+                    };
+                    if let Some(day) = usize::try_from(index).ok().and_then(|i| candidates.get(i)) {
+                        matches.push(*day);
+                    }
+                }
+            }
+        }
+        matches
+    }
+
+    fn is_in_month(&self, day: NaiveDate) -> bool {
+        self.by_month.is_empty() || self.by_month.contains(&day.month())
+    }
+
+    fn is_on_weekday(&self, day: NaiveDate) -> bool {
+        self.by_day.is_empty()
+            || self
+                .by_day
+                .iter()
+                .any(|(_, weekday)| *weekday == day.weekday())
+    }
+
+    fn is_on_month_day(&self, day: NaiveDate) -> bool {
+        let days_left = day
+            .checked_add_months(Months::new(1))
+            .and_then(|next| next.with_day(1))
+            .map_or(0, |next| (next - day).num_days() as i32);
+        self.by_month_day.is_empty()
+            || self
+                .by_month_day
+                .iter()
+                .any(|wanted| *wanted == day.day() as i32 || *wanted == -days_left)
+    }
+}
+
+/// A calendar file, re-read whenever it changes
+#[derive(Debug)]
+pub struct CalendarFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    pub events: Vec<Event>,
+}
+
+impl CalendarFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        CalendarFile {
+            path: path.into(),
+            modified: None,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Re-reads the file if it changed since the last read, returns whether it did.
+    /// The previous events are kept if it can't be read.
+    pub fn refresh(&mut self) -> io::Result<bool> {
+        if self.modified.is_some() && !self.is_changed() {
+            return Ok(false);
+        }
+        let content = fs::read_to_string(&self.path)?;
+        self.modified = modified(&self.path);
+        self.events = parse_calendar(&content);
+        Ok(true)
+    }
+
+    /// Whether the file changed since the last read
+    pub fn is_changed(&self) -> bool {
+        modified(&self.path) != self.modified
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Joins folded lines, which continue with a leading space or tab
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn parse_property(line: &str) -> Option<Property<'_>> {
+    // The value starts at the first colon outside of quoted parameters
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(index, char)| {
+        match char {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => return Some(index),
+            _ => {}
+        }
+        None
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.trim().to_uppercase(), value))
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value,
+    })
+}
+
+/// Builds an event from its properties. None for cancelled events.
+fn parse_event(properties: &[Property]) -> Result<Option<Event>, String> {
+    let get = |name: &str| properties.iter().find(|property| property.name == name);
+    let summary = get("SUMMARY").map_or_else(String::new, |summary| unescape(summary.value));
+
+    if get("STATUS").is_some_and(|status| status.value.eq_ignore_ascii_case("CANCELLED")) {
+        return Ok(None);
+    }
+
+    let dtstart = get("DTSTART").ok_or_else(|| format!("`{}` has no DTSTART", summary))?;
+    let invalid = |property: &Property| {
+        format!(
+            "`{}` has an invalid {} `{}`",
+            summary, property.name, property.value
+        )
+    };
+    let (start, frame) =
+        parse_time(dtstart.value, dtstart.param("TZID")).ok_or_else(|| invalid(dtstart))?;
+    let is_all_day = dtstart.param("VALUE") == Some("DATE") || dtstart.value.len() == 8;
+
+    let duration = match (get("DTEND"), get("DURATION")) {
+        (Some(dtend), _) => {
+            let (end, end_frame) =
+                parse_time(dtend.value, dtend.param("TZID")).ok_or_else(|| invalid(dtend))?;
+            frame.convert(end, end_frame) - start
+        }
+        (None, Some(duration)) => {
+            parse_ics_duration(duration.value).ok_or_else(|| invalid(duration))?
+        }
+        // All day events without an end last their day, others are instants
+        (None, None) if is_all_day => TimeDelta::days(1),
+        (None, None) => TimeDelta::zero(),
+    };
+
+    let recurrence = match get("RRULE") {
+        Some(rrule) => Some(
+            Recurrence::parse(rrule.value, frame)
+                .map_err(|err| format!("`{}` has an {}", summary, err))?,
+        ),
+        None => None,
+    };
+
+    let mut exdates = Vec::new();
+    for exdate in properties
+        .iter()
+        .filter(|property| property.name == "EXDATE")
+    {
+        for value in exdate.value.split(',') {
+            let (time, time_frame) =
+                parse_time(value, exdate.param("TZID")).ok_or_else(|| invalid(exdate))?;
+            // A date leaves out the occurrence on that day
+            let time = match value.len() {
+                8 => time.date().and_time(start.time()),
+                _ => frame.convert(time, time_frame),
+            };
+            exdates.push(time);
+        }
+    }
+
+    let recurrence_id = match get("RECURRENCE-ID") {
+        Some(id) => {
+            let (time, time_frame) =
+                parse_time(id.value, id.param("TZID")).ok_or_else(|| invalid(id))?;
+            Some(frame.convert(time, time_frame))
+        }
+        None => None,
+    };
+
+    let categories = properties
+        .iter()
+        .filter(|property| property.name == "CATEGORIES")
+        .flat_map(|property| split_list(property.value))
+        .collect();
+
+    Ok(Some(Event {
+        summary,
+        categories,
+        start,
+        frame,
+        duration,
+        recurrence,
+        exdates,
+        uid: get("UID").map(|uid| uid.value.to_string()),
+        recurrence_id,
+    }))
+}
+
+/// Parses `20251020`, `20251020T090000` and `20251020T090000Z`.
+/// Unknown TZIDs (e.g. Windows zone names) fall back to the schedule's zone.
+fn parse_time(value: &str, tzid: Option<&str>) -> Option<(NaiveDateTime, Frame)> {
+    let value = value.trim();
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((date.and_time(NaiveTime::MIN), Frame::Floating));
+    }
+    let (value, is_utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let frame = match (is_utc, tzid.and_then(|tzid| tzid.parse().ok())) {
+        (true, _) => Frame::Utc,
+        (false, Some(tz)) => Frame::Zone(tz),
+        (false, None) => Frame::Floating,
+    };
+    Some((time, frame))
+}
+
+/// Parses durations like `PT1H30M`, `P1D` or `P2W`
+fn parse_ics_duration(value: &str) -> Option<TimeDelta> {
+    let (sign, value) = match value.trim().strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value.trim().trim_start_matches('+')),
+    };
+    let value = value.strip_prefix('P')?;
+    let mut seconds = 0i64;
+    let mut number = String::new();
+    let mut is_time = false;
+    for char in value.chars() {
+        match char {
+            'T' => is_time = true,
+            '0'..='9' => number.push(char),
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                seconds += amount
+                    * match (unit, is_time) {
+                        ('W', false) => 604800,
+                        ('D', false) => 86400,
+                        ('H', true) => 3600,
+                        ('M', true) => 60,
+                        ('S', true) => 1,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+    number
+        .is_empty()
+        .then(|| TimeDelta::seconds(sign * seconds))
+}
+
+/// Parses a weekday with an optional ordinal, e.g. `MO`, `2TU` or `-1FR`
+fn parse_by_day(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let value = value.trim();
+    let split = value.len().checked_sub(2)?;
+    let weekday = match value.get(split..)?.to_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let ordinal = match &value[..split] {
+        "" => None,
+        ordinal => Some(
+            ordinal
+                .trim_start_matches('+')
+                .parse()
+                .ok()
+                .filter(|n| *n != 0)?,
+        ),
+    };
+    Some((ordinal, weekday))
+}
+
+/// Splits a comma separated text list, keeping escaped commas
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    items
+                        .last_mut()
+                        .unwrap()
+                        .push_str(&unescape(&format!("\\{}", next)));
+                }
+            }
+            ',' => items.push(String::new()),
+            _ => items.last_mut().unwrap().push(char),
+        }
+    }
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn unescape(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            text.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(escaped) => text.push(escaped),
+            None => {}
+        }
+    }
+    text.trim().to_string()
+}
@@ -0,0 +1,99 @@
+use crate::core::selection::stable_hash;
+use image::{codecs::jpeg::JpegEncoder, imageops, ImageBuffer, Rgb};
+use std::{
+    error::Error,
+    fs,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+const FRAME_QUALITY: u8 = 90;
+
+/// Default cache location of rendered frames: `~/.cache/expression/crossfade`
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|path| path.join("expression/crossfade"))
+}
+
+/// Returns frame `index` of `steps` blended from `from` towards `to`,
+/// rendering it into the cache first if needed.
+///
+/// Frames are cached per image pair and step count, and re-rendered when either image changes.
+/// Only the current pair is kept, starting a new pair removes the frames of older ones.
+pub fn get_frame(
+    cache_dir: &Path,
+    from: &Path,
+    to: &Path,
+    index: usize,
+    steps: usize,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let pair_dir = cache_dir.join(pair_key(from, to, steps)?);
+    let frame = pair_dir.join(format!("{:03}.jpg", index));
+    if frame.exists() {
+        return Ok(frame);
+    }
+
+    if !pair_dir.exists() {
+        prune_cache(cache_dir)?;
+        fs::create_dir_all(&pair_dir)?;
+    }
+    // Render to a temporary file so an interrupted render is never reused
+    let partial = pair_dir.join(format!("{:03}.part", index));
+    render_frame(from, to, index as f32 / steps as f32, &partial)?;
+    fs::rename(&partial, &frame)?;
+    Ok(frame)
+}
+
+/// Blends two images into a JPEG file.
+/// `alpha` of 0 is `from` and 1 is `to`; `to` is resized to `from` if their sizes differ.
+pub fn render_frame(
+    from: &Path,
+    to: &Path,
+    alpha: f32,
+    output: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let from = image::open(from)?.to_rgb8();
+    let mut to = image::open(to)?.to_rgb8();
+    let (width, height) = from.dimensions();
+    if to.dimensions() != (width, height) {
+        to = imageops::resize(&to, width, height, imageops::FilterType::Triangle);
+    }
+
+    let alpha = alpha.clamp(0.0, 1.0);
+    let blended = ImageBuffer::from_fn(width, height, |x, y| {
+        let (a, b) = (from.get_pixel(x, y), to.get_pixel(x, y));
+        Rgb(std::array::from_fn(|channel| {
+            (a[channel] as f32 * (1.0 - alpha) + b[channel] as f32 * alpha).round() as u8
+        }))
+    });
+
+    let file = BufWriter::new(fs::File::create(output)?);
+    blended.write_with_encoder(JpegEncoder::new_with_quality(file, FRAME_QUALITY))?;
+    Ok(())
+}
+
+/// Cache key of an image pair, changes whenever either image is modified
+fn pair_key(from: &Path, to: &Path, steps: usize) -> Result<String, Box<dyn Error>> {
+    let mut input = Vec::new();
+    for path in [from, to] {
+        let modified = fs::metadata(path)?.modified()?.duration_since(UNIX_EPOCH)?;
+        input.extend_from_slice(path.as_os_str().as_encoded_bytes());
+        input.extend_from_slice(format!("\0{}\0", modified.as_nanos()).as_bytes());
+    }
+    input.extend_from_slice(steps.to_string().as_bytes());
+    Ok(format!("{:016x}", stable_hash(&input)))
+}
+
+/// Removes the frames of all cached pairs
+fn prune_cache(cache_dir: &Path) -> Result<(), Box<dyn Error>> {
+    if !cache_dir.exists() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(cache_dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            fs::remove_dir_all(path)?;
+        }
+    }
+    Ok(())
+}
@@ -1 +1,9 @@
+pub mod calendar;
+pub mod crossfade;
+pub mod rotation;
+pub mod rules;
+pub mod schedule;
+pub mod selection;
+pub mod state;
+pub mod timeline;
 pub mod wallpaper;
@@ -0,0 +1,61 @@
+use crate::config::{CollectionScheduleConfig, RotationPeriod};
+use chrono::{Datelike, NaiveDate};
+
+// Looking further ahead than a year never finds a switch which a year didn't
+const LOOKAHEAD_DAYS: u32 = 366;
+
+/// Collection scheduled for a date: the month's collection, else the rotation's.
+///
+/// Rotations are counted from fixed dates, so every restart (and every machine) agrees
+/// on the current collection without keeping any state.
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use expression::config::{CollectionScheduleConfig, RotationPeriod};
+/// use expression::core::rotation::scheduled_collection;
+///
+/// let config = CollectionScheduleConfig {
+///     rotate: Some(RotationPeriod::Monthly),
+///     collections: vec!["Nature".into(), "Cities".into()],
+///     ..Default::default()
+/// };
+///
+/// let may = NaiveDate::from_ymd_opt(2025, 5, 20).unwrap();
+/// let june = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+/// assert_ne!(scheduled_collection(&config, may), scheduled_collection(&config, june));
+/// ```
+pub fn scheduled_collection(config: &CollectionScheduleConfig, date: NaiveDate) -> Option<&str> {
+    if let Some(collection) = config.month_collections.get(&date.month()) {
+        return Some(collection);
+    }
+    let period = config.rotate?;
+    let count = config.collections.len() as i64;
+    let index = period_index(period, date).rem_euclid(count.max(1));
+    config.collections.get(index as usize).map(String::as_str)
+}
+
+/// First date after `date` on which the scheduled collection changes,
+/// with the collection from then on (None if nothing is scheduled then).
+/// None if it never changes.
+pub fn next_switch(
+    config: &CollectionScheduleConfig,
+    date: NaiveDate,
+) -> Option<(NaiveDate, Option<&str>)> {
+    let current = scheduled_collection(config, date);
+    date.iter_days()
+        .skip(1)
+        .take(LOOKAHEAD_DAYS as usize)
+        .map(|day| (day, scheduled_collection(config, day)))
+        .find(|(_, collection)| *collection != current)
+}
+
+/// Number of whole periods since a fixed start
+fn period_index(period: RotationPeriod, date: NaiveDate) -> i64 {
+    match period {
+        RotationPeriod::Daily => date.num_days_from_ce() as i64,
+        // Day 1 (0001-01-01) was a Monday, so weeks switch on Mondays
+        RotationPeriod::Weekly => (date.num_days_from_ce() as i64 - 1).div_euclid(7),
+        RotationPeriod::Monthly => date.year() as i64 * 12 + date.month0() as i64,
+    }
+}
@@ -0,0 +1,472 @@
+use crate::utils::{cmd, system};
+use chrono::{DateTime, Datelike, TimeZone, Timelike};
+use log2::debug;
+use std::{cell::RefCell, collections::BTreeMap, fmt, path::Path, time::Duration};
+
+/// How long a probe command may run before it counts as false
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Built-in variables of rule conditions (besides `env.NAME` and `probe.NAME`)
+pub const VARIABLES: &[&str] = &[
+    "hour", "minute", "time", "weekday", "day", "month", "year", "date", "battery", "charging",
+    "outputs",
+];
+
+/// A `[[rules]]` entry: show `item` from the special collection while `condition` holds
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub condition: Condition,
+    pub item: String,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Returns the first rule whose condition holds, rules are tried in config order
+pub fn first_match<'a>(rules: &'a [Rule], variables: &dyn Fn(&str) -> Value) -> Option<&'a Rule> {
+    rules.iter().find(|rule| rule.condition.evaluate(variables))
+}
+
+/// Value of a variable or literal in a condition
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    /// Unknown on this machine (e.g. `battery` without a battery), every comparison fails
+    Missing,
+}
+
+impl Value {
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Bool(value) => *value,
+            Value::Number(value) => *value != 0.0,
+            Value::Text(value) => !value.is_empty(),
+            Value::Missing => false,
+        }
+    }
+
+    fn compare(&self, op: CompareOp, other: &Value) -> bool {
+        let ordering = match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+            (Value::Text(a), Value::Text(b)) => Some(a.to_lowercase().cmp(&b.to_lowercase())),
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            _ => None,
+        };
+        let Some(ordering) = ordering else {
+            return false;
+        };
+        match op {
+            CompareOp::Eq => ordering.is_eq(),
+            CompareOp::Ne => ordering.is_ne(),
+            CompareOp::Lt => ordering.is_lt(),
+            CompareOp::Le => ordering.is_le(),
+            CompareOp::Gt => ordering.is_gt(),
+            CompareOp::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Literal(Value),
+    Variable(String),
+    /// `exists("~/.vpn-on")`
+    Exists(String),
+}
+
+/// Parsed rule condition, e.g. `battery < 20 && !charging`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Or(Box<Condition>, Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+    Compare(Operand, CompareOp, Operand),
+    /// `weekday in ["sat", "sun"]`
+    In(Operand, Vec<Operand>),
+    /// Holds if the operand is true, non-zero or non-empty
+    Truthy(Operand),
+}
+
+impl Condition {
+    /// Parses a condition.
+    ///
+    /// Supports `||`, `&&`, `!`, parentheses, comparisons (`==`, `!=`, `<`, `<=`, `>`, `>=`),
+    /// `in [...]` lists, numbers, `"text"`, times (`09:30`), `true`/`false` and `exists("path")`.
+    ///
+    /// # Example
+    /// ```
+    /// use expression::core::rules::{Condition, Value};
+    ///
+    /// let condition = Condition::parse("battery < 20 && !charging").unwrap();
+    /// let variables = |name: &str| match name {
+    ///     "battery" => Value::Number(15.0),
+    ///     _ => Value::Bool(false),
+    /// };
+    /// assert!(condition.evaluate(&variables));
+    /// ```
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let condition = parser.or()?;
+        match parser.peek() {
+            None => Ok(condition),
+            Some(token) => Err(format!("unexpected `{}`", token)),
+        }
+    }
+
+    pub fn evaluate(&self, variables: &dyn Fn(&str) -> Value) -> bool {
+        match self {
+            Condition::Or(a, b) => a.evaluate(variables) || b.evaluate(variables),
+            Condition::And(a, b) => a.evaluate(variables) && b.evaluate(variables),
+            Condition::Not(condition) => !condition.evaluate(variables),
+            Condition::Compare(a, op, b) => a.value(variables).compare(*op, &b.value(variables)),
+            Condition::In(operand, list) => {
+                let value = operand.value(variables);
+                list.iter()
+                    .any(|item| value.compare(CompareOp::Eq, &item.value(variables)))
+            }
+            Condition::Truthy(operand) => operand.value(variables).is_truthy(),
+        }
+    }
+
+    /// Names of the probes used by the condition (`probe.NAME`)
+    pub fn probes(&self) -> Vec<&str> {
+        let mut probes = Vec::new();
+        self.visit_operands(&mut |operand| {
+            if let Operand::Variable(name) = operand {
+                if let Some(probe) = name.strip_prefix("probe.") {
+                    probes.push(probe);
+                }
+            }
+        });
+        probes
+    }
+
+    fn visit_operands<'a>(&'a self, visit: &mut dyn FnMut(&'a Operand)) {
+        match self {
+            Condition::Or(a, b) | Condition::And(a, b) => {
+                a.visit_operands(visit);
+                b.visit_operands(visit);
+            }
+            Condition::Not(condition) => condition.visit_operands(visit),
+            Condition::Compare(a, _, b) => {
+                visit(a);
+                visit(b);
+            }
+            Condition::In(operand, list) => {
+                visit(operand);
+                for item in list {
+                    visit(item);
+                }
+            }
+            Condition::Truthy(operand) => visit(operand),
+        }
+    }
+}
+
+impl Operand {
+    fn value(&self, variables: &dyn Fn(&str) -> Value) -> Value {
+        match self {
+            Operand::Literal(value) => value.clone(),
+            Operand::Variable(name) => variables(name),
+            Operand::Exists(path) => {
+                Value::Bool(Path::new(shellexpand::tilde(path).as_ref()).exists())
+            }
+        }
+    }
+}
+
+// █░█ ▄▀█ █▀█ █ ▄▀█ █▄▄ █░░ █▀▀ █▀
+// ▀▄▀ █▀█ █▀▄ █ █▀█ █▄█ █▄▄ ██▄ ▄█
+
+/// Built-in variables of this machine at a point in time.
+/// Probes run at most once per context, and only if a condition asks for them.
+pub struct SystemVariables<'a, Tz: TimeZone> {
+    now: DateTime<Tz>,
+    probes: &'a BTreeMap<String, String>,
+    probe_results: RefCell<BTreeMap<String, bool>>,
+}
+
+impl<'a, Tz: TimeZone> SystemVariables<'a, Tz> {
+    pub fn new(now: DateTime<Tz>, probes: &'a BTreeMap<String, String>) -> Self {
+        Self {
+            now,
+            probes,
+            probe_results: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Value {
+        let now = &self.now;
+        match name {
+            "hour" => Value::Number(now.hour() as f64),
+            "minute" => Value::Number(now.minute() as f64),
+            // Minutes of the day, compared with time literals like `09:30`
+            "time" => Value::Number((now.hour() * 60 + now.minute()) as f64),
+            "weekday" => Value::Text(now.weekday().to_string().to_lowercase()),
+            "day" => Value::Number(now.day() as f64),
+            "month" => Value::Number(now.month() as f64),
+            "year" => Value::Number(now.year() as f64),
+            "date" => Value::Text(now.date_naive().format("%m-%d").to_string()),
+            "battery" => {
+                system::battery().map_or(Value::Missing, |(level, _)| Value::Number(level))
+            }
+            "charging" => {
+                system::battery().map_or(Value::Missing, |(_, charging)| Value::Bool(charging))
+            }
+            "outputs" => {
+                system::output_count().map_or(Value::Missing, |count| Value::Number(count as f64))
+            }
+            _ => {
+                if let Some(variable) = name.strip_prefix("env.") {
+                    return std::env::var(variable).map_or(Value::Missing, Value::Text);
+                }
+                if let Some(probe) = name.strip_prefix("probe.") {
+                    return Value::Bool(self.probe(probe));
+                }
+                Value::Missing
+            }
+        }
+    }
+
+    /// Runs a probe command, which holds if it exits with 0 within `PROBE_TIMEOUT`
+    fn probe(&self, name: &str) -> bool {
+        if let Some(result) = self.probe_results.borrow().get(name) {
+            return *result;
+        }
+        let result = self
+            .probes
+            .get(name)
+            .and_then(|command| cmd::execute_timeout(command, PROBE_TIMEOUT).ok())
+            .flatten()
+            .is_some_and(|status| status.success());
+        debug!("Probe {}: {}", name, result);
+        self.probe_results
+            .borrow_mut()
+            .insert(name.to_string(), result);
+        result
+    }
+}
+
+// █▀█ ▄▀█ █▀█ █▀ █▀▀ █▀█
+// █▀▀ █▀█ █▀▄ ▄█ ██▄ █▀▄
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Text(value) => write!(f, "\"{}\"", value),
+            Token::Ident(name) => write!(f, "{}", name),
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+        }
+    }
+}
+
+// Longest first, so `<=` is not read as `<`
+const SYMBOLS: &[&str] = &[
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "!", "(", ")", "[", "]", ",",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            rest = &rest[symbol.len()..];
+        } else if c == '"' || c == '\'' {
+            let end = rest[1..]
+                .find(c)
+                .ok_or_else(|| format!("unclosed string {}", rest))?;
+            tokens.push(Token::Text(rest[1..end + 1].to_string()));
+            rest = &rest[end + 2..];
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.' && c != ':')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Number(parse_number(&rest[..end])?));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_' && c != '.')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            return Err(format!("unexpected `{}`", c));
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Numbers, or times of day as minutes (`09:30` is 570)
+fn parse_number(literal: &str) -> Result<f64, String> {
+    let invalid = || format!("invalid number `{}`", literal);
+    match literal.split_once(':') {
+        Some((hours, minutes)) => {
+            let hours: u32 = hours.parse().map_err(|_| invalid())?;
+            let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+            if hours > 24 || minutes > 59 {
+                return Err(format!("invalid time `{}`", literal));
+            }
+            Ok((hours * 60 + minutes) as f64)
+        }
+        None => literal.parse().map_err(|_| invalid()),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(next)) if *next == symbol) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat(symbol) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(token) => Err(format!("expected `{}`, found `{}`", symbol, token)),
+            None => Err(format!("expected `{}` at the end", symbol)),
+        }
+    }
+
+    fn or(&mut self) -> Result<Condition, String> {
+        let mut condition = self.and()?;
+        while self.eat("||") {
+            condition = Condition::Or(Box::new(condition), Box::new(self.and()?));
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<Condition, String> {
+        let mut condition = self.not()?;
+        while self.eat("&&") {
+            condition = Condition::And(Box::new(condition), Box::new(self.not()?));
+        }
+        Ok(condition)
+    }
+
+    fn not(&mut self) -> Result<Condition, String> {
+        if self.eat("!") {
+            return Ok(Condition::Not(Box::new(self.not()?)));
+        }
+        if self.eat("(") {
+            let condition = self.or()?;
+            self.expect(")")?;
+            return Ok(condition);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Condition, String> {
+        let left = self.operand()?;
+        let op = match self.peek() {
+            Some(Token::Symbol("==")) => CompareOp::Eq,
+            Some(Token::Symbol("!=")) => CompareOp::Ne,
+            Some(Token::Symbol("<")) => CompareOp::Lt,
+            Some(Token::Symbol("<=")) => CompareOp::Le,
+            Some(Token::Symbol(">")) => CompareOp::Gt,
+            Some(Token::Symbol(">=")) => CompareOp::Ge,
+            Some(Token::Ident(keyword)) if keyword == "in" => {
+                self.position += 1;
+                return Ok(Condition::In(left, self.list()?));
+            }
+            _ => return Ok(Condition::Truthy(left)),
+        };
+        self.position += 1;
+        Ok(Condition::Compare(left, op, self.operand()?))
+    }
+
+    fn list(&mut self) -> Result<Vec<Operand>, String> {
+        self.expect("[")?;
+        let mut items = Vec::new();
+        while !self.eat("]") {
+            items.push(self.operand()?);
+            if !self.eat(",") {
+                self.expect("]")?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    fn operand(&mut self) -> Result<Operand, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Operand::Literal(Value::Number(value))),
+            Some(Token::Text(value)) => Ok(Operand::Literal(Value::Text(value))),
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Operand::Literal(Value::Bool(true))),
+                "false" => Ok(Operand::Literal(Value::Bool(false))),
+                "exists" => {
+                    self.expect("(")?;
+                    let path = match self.next() {
+                        Some(Token::Text(path)) => path,
+                        _ => return Err("exists() takes a path in quotes".into()),
+                    };
+                    self.expect(")")?;
+                    Ok(Operand::Exists(path))
+                }
+                _ if VARIABLES.contains(&name.as_str())
+                    || name
+                        .strip_prefix("env.")
+                        .is_some_and(|rest| !rest.is_empty())
+                    || name
+                        .strip_prefix("probe.")
+                        .is_some_and(|rest| !rest.is_empty()) =>
+                {
+                    Ok(Operand::Variable(name))
+                }
+                _ => Err(format!("unknown variable `{}`", name)),
+            },
+            Some(token) => Err(format!("unexpected `{}`", token)),
+            None => Err("unexpected end".into()),
+        }
+    }
+}
@@ -0,0 +1,597 @@
+use crate::config::SpecialEntryConfig;
+use crate::utils::{
+    astro::AstroCondition,
+    calc::parse_duration,
+    cron::CronSchedule,
+    solar::{self, Location, SolarAnchor},
+};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Timelike};
+use log2::debug;
+use std::{collections::BTreeMap, error::Error, fmt};
+
+const SECONDS_PER_DAY: u32 = 86400;
+
+/// Calendar part of a special entry key
+#[derive(Debug, Clone, PartialEq)]
+pub enum DateSpec {
+    /// Recurring every year, e.g. `12-25` or `--12-25`
+    MonthDay(u32, u32),
+    /// A single calendar date, e.g. `2026-11-01`
+    Date(NaiveDate),
+    /// Recurring range, wraps across new year, e.g. `12-20..01-05`
+    MonthDayRange((u32, u32), (u32, u32)),
+    /// Fixed range of calendar dates, e.g. `2026-12-20..2027-01-05`
+    DateRange(NaiveDate, NaiveDate),
+    /// Days on which an astronomical condition holds, e.g. `moon:full` or `solstice`
+    Astro(AstroCondition),
+}
+
+/// Time of day part of a special entry key
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimeSpec {
+    /// Active for the whole hour, e.g. `23`
+    Hour(u8),
+    /// Active between two times of the day in seconds (end exclusive),
+    /// wraps past midnight if end is before start, e.g. `22:30-06:00`
+    Range(u32, u32),
+    /// Active for an hour from a solar event, e.g. `sunset-30m`
+    Solar(SolarAnchor),
+    /// Active for a duration from each firing, e.g. `cron = "0 9 * * 1-5"`
+    Cron(CronSchedule, Duration),
+}
+
+/// Precedence tier of a special entry. Higher tiers win.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Precedence {
+    /// Time only, e.g. `23`, `sunset` or a cron schedule
+    Hourly,
+    /// Date only, active all day, e.g. `12-25`
+    Dated,
+    /// Date combined with a time, e.g. `12-25 09`
+    DatedHourly,
+}
+
+impl fmt::Display for Precedence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Precedence::Hourly => "hourly",
+            Precedence::Dated => "dated",
+            Precedence::DatedHourly => "dated hourly",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A parsed `[special_entries]` line: `key = "item"`
+#[derive(Debug, Clone)]
+pub struct SpecialEntry {
+    pub key: String,
+    pub date: Option<DateSpec>,
+    pub time: Option<TimeSpec>,
+    pub item: String,
+}
+
+impl SpecialEntry {
+    pub fn precedence(&self) -> Precedence {
+        match (&self.date, &self.time) {
+            (Some(_), Some(_)) => Precedence::DatedHourly,
+            (Some(_), None) => Precedence::Dated,
+            _ => Precedence::Hourly,
+        }
+    }
+
+    /// Checks if the entry is active at the given time.
+    /// Solar entries are never active without a location.
+    pub fn is_active<Tz: TimeZone<Offset: Copy>>(
+        &self,
+        now: DateTime<Tz>,
+        location: Option<&Location>,
+    ) -> bool {
+        let seconds = now.num_seconds_from_midnight();
+        // Past midnight part of a wrapping range belongs to the day it started
+        let date = match &self.time {
+            Some(TimeSpec::Range(start, end)) if start > end && seconds < *end => {
+                now.date_naive().pred_opt().unwrap_or(now.date_naive())
+            }
+            _ => now.date_naive(),
+        };
+        let date_match = match &self.date {
+            Some(spec) => date_matches(spec, date),
+            None => true,
+        };
+        let time_match = match &self.time {
+            Some(TimeSpec::Hour(hour)) => now.hour() == *hour as u32,
+            Some(TimeSpec::Range(start, end)) => in_range(seconds, *start, *end),
+            Some(TimeSpec::Solar(anchor)) => location
+                .and_then(|location| solar::slot_start(anchor, now, location, solar::SLOT_SECONDS))
+                .is_some(),
+            Some(TimeSpec::Cron(schedule, duration)) => {
+                schedule.active_firing(now, *duration).is_some()
+            }
+            None => true,
+        };
+        date_match && time_match
+    }
+
+    /// Number of days covered by the date part (narrower dates are more specific)
+    fn date_span(&self) -> i64 {
+        match &self.date {
+            Some(DateSpec::MonthDayRange(start, end)) => {
+                let (start, end) = (leap_ordinal(*start), leap_ordinal(*end));
+                if start <= end {
+                    end - start + 1
+                } else {
+                    366 - start + end + 1
+                }
+            }
+            Some(DateSpec::DateRange(start, end)) => (*end - *start).num_days() + 1,
+            Some(DateSpec::Astro(condition)) => condition.span_days(),
+            _ => 1,
+        }
+    }
+
+    /// Number of seconds covered by the time part (shorter windows are more specific)
+    fn time_span(&self) -> i64 {
+        match &self.time {
+            Some(TimeSpec::Range(start, end)) => {
+                ((*end + SECONDS_PER_DAY - *start) % SECONDS_PER_DAY) as i64
+            }
+            Some(TimeSpec::Cron(_, duration)) => duration.num_seconds(),
+            Some(_) => solar::SLOT_SECONDS,
+            None => 86400,
+        }
+    }
+
+    /// Start and end of the entry's currently active window, if active.
+    /// Date only entries span the whole day.
+    pub fn active_window<Tz: TimeZone<Offset: Copy>>(
+        &self,
+        now: DateTime<Tz>,
+        location: Option<&Location>,
+    ) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
+        if !self.is_active(now, location) {
+            return None;
+        }
+        let zone = now.timezone();
+        let today = now.date_naive();
+        let seconds = now.num_seconds_from_midnight();
+        match &self.time {
+            Some(TimeSpec::Hour(hour)) => {
+                let start = at_time_of_day(&zone, today, *hour as u32 * 3600)?;
+                Some((start, start + Duration::hours(1)))
+            }
+            Some(TimeSpec::Range(start, _)) => {
+                let date = if *start > seconds {
+                    today.pred_opt()?
+                } else {
+                    today
+                };
+                let start = at_time_of_day(&zone, date, *start)?;
+                Some((start, start + Duration::seconds(self.time_span())))
+            }
+            Some(TimeSpec::Solar(anchor)) => {
+                let start = solar::slot_start(anchor, now, location?, solar::SLOT_SECONDS)?;
+                Some((start, start + Duration::seconds(solar::SLOT_SECONDS)))
+            }
+            Some(TimeSpec::Cron(schedule, duration)) => {
+                let start = schedule.active_firing(now, *duration)?;
+                Some((start, start + *duration))
+            }
+            None => Some((
+                at_time_of_day(&zone, today, 0)?,
+                at_time_of_day(&zone, today.succ_opt()?, 0)?,
+            )),
+        }
+    }
+
+    /// Fixed daily windows of the entry in seconds, split at midnight
+    fn daily_windows(&self) -> Vec<(u32, u32)> {
+        let (start, end) = match &self.time {
+            Some(TimeSpec::Hour(hour)) => (*hour as u32 * 3600, (*hour as u32 + 1) * 3600),
+            Some(TimeSpec::Range(start, end)) => (*start, *end),
+            _ => return Vec::new(),
+        };
+        if start < end {
+            vec![(start, end)]
+        } else {
+            vec![(start, SECONDS_PER_DAY), (0, end)]
+        }
+    }
+}
+
+/// Parses all `[special_entries]` keys into special entries.
+///
+/// Supported keys:
+/// - `HH`: hour of the day (e.g. `23`)
+/// - `HH:MM-HH:MM`: range of time, may wrap past midnight (e.g. `12:00-14:00`, `22:30-06:00`)
+/// - `MM-DD` or `--MM-DD`: every year on that date (e.g. `12-25`, `--07-04`)
+/// - `YYYY-MM-DD`: on that date only (e.g. `2026-11-01`)
+/// - `DATE..DATE`: range of dates, both sides in the same format (e.g. `12-20..12-31`)
+/// - `SOLAR`: solar event with an optional offset (e.g. `sunrise`, `sunset-30m`)
+/// - `DATE TIME`: any of the above dates combined with a time (e.g. `12-25 09`)
+///
+/// `NN-NN` keys with both numbers up to 24 (e.g. `12-14`) are rejected as ambiguous,
+/// such dates need the `--MM-DD` form and time ranges need minutes on both ends.
+/// Entries with a `cron` value use the key as a plain label instead.
+///
+/// Fixed time windows (hours and ranges) of entries with the same date must not overlap.
+pub fn parse_special_entries(
+    entries: &BTreeMap<String, SpecialEntryConfig>,
+) -> Result<Vec<SpecialEntry>, Box<dyn Error>> {
+    let mut special_entries = Vec::new();
+    for (key, value) in entries {
+        let invalid = |err: String| format!("Invalid special entry \"{}\": {}", key, err);
+        let entry = match value {
+            SpecialEntryConfig::Item(item) => {
+                let (date, time) = parse_key(key).map_err(invalid)?;
+                SpecialEntry {
+                    key: key.clone(),
+                    date,
+                    time,
+                    item: item.clone(),
+                }
+            }
+            SpecialEntryConfig::Cron {
+                cron,
+                item,
+                duration,
+            } => {
+                let schedule = CronSchedule::parse(cron).map_err(invalid)?;
+                let duration = match duration {
+                    Some(duration) => parse_duration(duration)
+                        .filter(|seconds| *seconds > 0)
+                        .ok_or_else(|| invalid(format!("invalid duration `{}`", duration)))?,
+                    None => solar::SLOT_SECONDS as u64,
+                };
+                SpecialEntry {
+                    key: key.clone(),
+                    date: None,
+                    time: Some(TimeSpec::Cron(schedule, Duration::seconds(duration as i64))),
+                    item: item.clone().unwrap_or_else(|| key.clone()),
+                }
+            }
+        };
+        special_entries.push(entry);
+    }
+    check_overlaps(&special_entries)?;
+    Ok(special_entries)
+}
+
+/// Reports entries with the same date whose fixed time windows overlap
+fn check_overlaps(entries: &[SpecialEntry]) -> Result<(), Box<dyn Error>> {
+    for (index, entry) in entries.iter().enumerate() {
+        for other in entries.iter().skip(index + 1) {
+            if entry.date != other.date {
+                continue;
+            }
+            let overlaps = entry.daily_windows().iter().any(|(start, end)| {
+                other
+                    .daily_windows()
+                    .iter()
+                    .any(|(other_start, other_end)| start < other_end && other_start < end)
+            });
+            if overlaps {
+                return Err(format!(
+                    "Special entries \"{}\" and \"{}\" overlap",
+                    entry.key, other.key
+                )
+                .into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Finds the special entry to use at the given time.
+///
+/// Precedence: dated hourly > dated > hourly.
+/// Within the same tier, the narrower date wins (e.g. `12-25` over `12-20..12-31`),
+/// then the shorter time window (e.g. a 15 minute cron entry over an hour).
+pub fn resolve_special<'a, Tz: TimeZone<Offset: Copy>>(
+    entries: &'a [SpecialEntry],
+    now: DateTime<Tz>,
+    location: Option<&Location>,
+) -> Option<&'a SpecialEntry> {
+    let mut active: Vec<&SpecialEntry> = entries
+        .iter()
+        .filter(|e| e.is_active(now, location))
+        .collect();
+    // Stable sort keeps config order for equally specific entries
+    active.sort_by_key(|e| {
+        (
+            std::cmp::Reverse(e.precedence()),
+            e.date_span(),
+            e.time_span(),
+        )
+    });
+
+    let winner = active.first()?;
+    for other in active.iter().skip(1) {
+        debug!(
+            "Special entry \"{}\" ({}) takes precedence over \"{}\" ({})",
+            winner.key,
+            winner.precedence(),
+            other.key,
+            other.precedence()
+        );
+    }
+    Some(winner)
+}
+
+/// Collects solar anchors used by special entries (for wake up calculation)
+pub fn solar_anchors(entries: &[SpecialEntry]) -> Vec<SolarAnchor> {
+    entries
+        .iter()
+        .filter_map(|entry| match &entry.time {
+            Some(TimeSpec::Solar(anchor)) => Some(*anchor),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Finds the next time after `now` at which a range, solar or cron entry starts or ends.
+/// Used to wake up precisely instead of at the next hour.
+pub fn next_boundary<Tz: TimeZone<Offset: Copy>>(
+    entries: &[SpecialEntry],
+    now: DateTime<Tz>,
+    location: Option<&Location>,
+) -> Option<DateTime<Tz>> {
+    let anchors = solar_anchors(entries);
+    let solar_boundary = location
+        .and_then(|location| solar::next_boundary(&anchors, now, location, solar::SLOT_SECONDS));
+
+    let cron_boundary = entries
+        .iter()
+        .filter_map(|entry| match &entry.time {
+            Some(TimeSpec::Cron(schedule, duration)) => {
+                let end = schedule
+                    .active_firing(now, *duration)
+                    .map(|firing| firing + *duration);
+                let next = schedule.next_after(now);
+                end.into_iter().chain(next).min()
+            }
+            _ => None,
+        })
+        .min();
+
+    let range_boundary = entries
+        .iter()
+        .filter_map(|entry| match &entry.time {
+            Some(TimeSpec::Range(start, end)) => [*start, *end]
+                .into_iter()
+                .filter_map(|seconds| next_time_of_day(now, seconds))
+                .min(),
+            _ => None,
+        })
+        .min();
+
+    [solar_boundary, cron_boundary, range_boundary]
+        .into_iter()
+        .flatten()
+        .min()
+}
+
+/// Finds the next time after `now` at which any entry becomes active.
+/// Used to end a group's active window before a special entry takes over.
+pub fn next_activation<Tz: TimeZone<Offset: Copy>>(
+    entries: &[SpecialEntry],
+    now: DateTime<Tz>,
+    location: Option<&Location>,
+) -> Option<DateTime<Tz>> {
+    let zone = now.timezone();
+    let today = now.date_naive();
+    let dates = [Some(today), today.succ_opt()];
+    entries
+        .iter()
+        .flat_map(|entry| {
+            let candidates: Vec<DateTime<Tz>> = match &entry.time {
+                Some(TimeSpec::Hour(hour)) => dates
+                    .iter()
+                    .flatten()
+                    .filter_map(|date| at_time_of_day(&zone, *date, *hour as u32 * 3600))
+                    .collect(),
+                Some(TimeSpec::Range(start, _)) => dates
+                    .iter()
+                    .flatten()
+                    .filter_map(|date| at_time_of_day(&zone, *date, *start))
+                    .collect(),
+                Some(TimeSpec::Solar(anchor)) => match location {
+                    Some(location) => dates
+                        .iter()
+                        .flatten()
+                        .filter_map(|date| anchor.time_on(*date, location, &zone))
+                        .collect(),
+                    None => Vec::new(),
+                },
+                Some(TimeSpec::Cron(schedule, _)) => schedule.next_after(now).into_iter().collect(),
+                None => dates
+                    .iter()
+                    .flatten()
+                    .filter_map(|date| date.succ_opt())
+                    .filter_map(|date| at_time_of_day(&zone, date, 0))
+                    .collect(),
+            };
+            candidates
+                .into_iter()
+                .filter(|time| *time > now && entry.is_active(*time, location))
+                .min()
+        })
+        .min()
+}
+
+fn at_time_of_day<Tz: TimeZone>(zone: &Tz, date: NaiveDate, seconds: u32) -> Option<DateTime<Tz>> {
+    let time = NaiveTime::from_num_seconds_from_midnight_opt(seconds % SECONDS_PER_DAY, 0)?;
+    zone.from_local_datetime(&date.and_time(time)).earliest()
+}
+
+/// First local time after `now` at the given seconds of the day (today or tomorrow)
+fn next_time_of_day<Tz: TimeZone<Offset: Copy>>(
+    now: DateTime<Tz>,
+    seconds: u32,
+) -> Option<DateTime<Tz>> {
+    let zone = now.timezone();
+    let today = now.date_naive();
+    [Some(today), today.succ_opt()]
+        .into_iter()
+        .flatten()
+        .filter_map(|date| at_time_of_day(&zone, date, seconds))
+        .find(|time| *time > now)
+}
+
+fn in_range(seconds: u32, start: u32, end: u32) -> bool {
+    if start < end {
+        start <= seconds && seconds < end
+    } else {
+        // Wraps past midnight
+        seconds >= start || seconds < end
+    }
+}
+
+fn parse_key(key: &str) -> Result<(Option<DateSpec>, Option<TimeSpec>), String> {
+    let parts: Vec<&str> = key.split_whitespace().collect();
+    match parts.as_slice() {
+        [single] => {
+            if single.chars().all(|c| c.is_ascii_digit()) {
+                Ok((None, Some(parse_time(single)?)))
+            } else if let Some(anchor) = SolarAnchor::parse(single) {
+                Ok((None, Some(TimeSpec::Solar(anchor))))
+            } else if let Some(range) = parse_time_range(single) {
+                Ok((None, Some(range?)))
+            } else if let Some((start, end)) = ambiguous_parts(single) {
+                Err(format!(
+                    "ambiguous key, write `--{}` for a date or `{:02}:00-{:02}:00` for a time range",
+                    single, start, end
+                ))
+            } else {
+                Ok((Some(parse_date_spec(single)?), None))
+            }
+        }
+        [date, time] => Ok((Some(parse_date_spec(date)?), Some(parse_time(time)?))),
+        _ => Err("expected `TIME`, `DATE` or `DATE TIME`".into()),
+    }
+}
+
+/// Numbers of an `NN-NN` key which reads as a date and as an hour range (both up to 24), e.g. `12-14`
+fn ambiguous_parts(value: &str) -> Option<(u8, u8)> {
+    let (start, end) = value.split_once('-')?;
+    let hour = |part: &str| {
+        let is_number = (1..=2).contains(&part.len()) && part.chars().all(|c| c.is_ascii_digit());
+        is_number
+            .then(|| part.parse::<u8>().ok())
+            .flatten()
+            .filter(|hour| *hour <= 24)
+    };
+    Some((hour(start)?, hour(end)?))
+}
+
+fn parse_time(value: &str) -> Result<TimeSpec, String> {
+    if let Some(anchor) = SolarAnchor::parse(value) {
+        return Ok(TimeSpec::Solar(anchor));
+    }
+    if let Some(range) = parse_time_range(value) {
+        return range;
+    }
+    match value.parse::<u8>() {
+        Ok(hour) if hour < 24 => Ok(TimeSpec::Hour(hour)),
+        _ => Err(format!("invalid hour `{}`", value)),
+    }
+}
+
+/// Parses `HH:MM-HH:MM` time ranges.
+/// Returns None if the value doesn't look like a time range (e.g. `12-25` is a date).
+fn parse_time_range(value: &str) -> Option<Result<TimeSpec, String>> {
+    let (start, end) = value.split_once('-')?;
+    let (start, end) = (parse_time_of_day(start)?, parse_time_of_day(end)?);
+    if start >= SECONDS_PER_DAY {
+        return Some(Err(format!("range `{}` can't start at 24:00", value)));
+    }
+    if start == end % SECONDS_PER_DAY {
+        return Some(Err(format!("range `{}` is empty", value)));
+    }
+    Some(Ok(TimeSpec::Range(start, end)))
+}
+
+/// Parses `HH:MM` into seconds from midnight (24:00 allowed)
+fn parse_time_of_day(value: &str) -> Option<u32> {
+    let (hour, minute) = value.split_once(':')?;
+    if minute.len() != 2 {
+        return None;
+    }
+    let minute = minute.parse::<u32>().ok()?;
+    if hour.is_empty() || hour.len() > 2 {
+        return None;
+    }
+    let hour = hour.parse::<u32>().ok()?;
+    let seconds = hour * 3600 + minute * 60;
+    (minute < 60 && seconds <= SECONDS_PER_DAY).then_some(seconds)
+}
+
+fn parse_date_spec(value: &str) -> Result<DateSpec, String> {
+    if let Some(condition) = AstroCondition::parse(value) {
+        return condition.map(DateSpec::Astro);
+    }
+    if let Some((start, end)) = value.split_once("..") {
+        return match (parse_date(start)?, parse_date(end)?) {
+            (DateSpec::MonthDay(sm, sd), DateSpec::MonthDay(em, ed)) => {
+                Ok(DateSpec::MonthDayRange((sm, sd), (em, ed)))
+            }
+            (DateSpec::Date(start), DateSpec::Date(end)) => {
+                if start > end {
+                    return Err(format!("range `{}` ends before it starts", value));
+                }
+                Ok(DateSpec::DateRange(start, end))
+            }
+            _ => Err(format!("both ends of `{}` must use the same format", value)),
+        };
+    }
+    parse_date(value)
+}
+
+fn parse_date(value: &str) -> Result<DateSpec, String> {
+    let value = value.strip_prefix("--").unwrap_or(value);
+    let parts: Vec<&str> = value.split('-').collect();
+    let number = |s: &str| {
+        s.parse::<u32>()
+            .map_err(|_| format!("invalid date `{}`", value))
+    };
+    match parts.as_slice() {
+        [month, day] => {
+            let (month, day) = (number(month)?, number(day)?);
+            // NOTE: Leap year so that 02-29 is accepted
+            NaiveDate::from_ymd_opt(2000, month, day)
+                .map(|_| DateSpec::MonthDay(month, day))
+                .ok_or_else(|| format!("invalid date `{}`", value))
+        }
+        [year, month, day] => {
+            NaiveDate::from_ymd_opt(number(year)? as i32, number(month)?, number(day)?)
+                .map(DateSpec::Date)
+                .ok_or_else(|| format!("invalid date `{}`", value))
+        }
+        _ => Err(format!("invalid date `{}`", value)),
+    }
+}
+
+fn date_matches(spec: &DateSpec, today: NaiveDate) -> bool {
+    let month_day = (today.month(), today.day());
+    match spec {
+        DateSpec::MonthDay(month, day) => month_day == (*month, *day),
+        DateSpec::Date(date) => today == *date,
+        DateSpec::MonthDayRange(start, end) => {
+            if start <= end {
+                *start <= month_day && month_day <= *end
+            } else {
+                // Wraps across new year
+                month_day >= *start || month_day <= *end
+            }
+        }
+        DateSpec::DateRange(start, end) => *start <= today && today <= *end,
+        DateSpec::Astro(condition) => condition.is_met(today),
+    }
+}
+
+/// Day of a leap year for a (month, day) pair
+fn leap_ordinal((month, day): (u32, u32)) -> i64 {
+    NaiveDate::from_ymd_opt(2000, month, day)
+        .map(|date| date.ordinal() as i64)
+        .unwrap_or(0)
+}
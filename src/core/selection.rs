@@ -0,0 +1,163 @@
+use crate::core::state::{SequentialPosition, ShuffleBag};
+use chrono::NaiveDate;
+use rand::seq::SliceRandom;
+use std::{collections::BTreeMap, path::Path};
+
+// FNV-1a, stable across platforms and releases unlike std's hasher
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Draws the next wallpaper from a shuffle bag.
+///
+/// Every wallpaper is shown once per cycle before the group is reshuffled,
+/// and the last wallpaper of a cycle never opens the next one.
+/// Wallpapers removed from the group are dropped, new ones join from the next cycle.
+///
+/// Returns the index of the drawn wallpaper in `wallpapers`.
+pub fn shuffle_index(bag: &mut ShuffleBag, wallpapers: &[String]) -> usize {
+    if wallpapers.is_empty() {
+        return 0;
+    }
+    bag.remaining
+        .retain(|wallpaper| wallpapers.contains(wallpaper));
+    if bag.remaining.is_empty() {
+        let mut order = wallpapers.to_vec();
+        order.shuffle(&mut rand::rng());
+        // Avoid an immediate repeat at the wrap point
+        if order.len() > 1 && order.first() == bag.last.as_ref() {
+            let swap = rand::random_range(1..order.len());
+            order.swap(0, swap);
+        }
+        bag.remaining = order;
+    }
+
+    let wallpaper = bag.remaining.remove(0);
+    let index = wallpapers
+        .iter()
+        .position(|candidate| *candidate == wallpaper)
+        .unwrap_or(0);
+    bag.last = Some(wallpaper);
+    index
+}
+
+/// Picks the wallpaper after the last shown one, once per activation of the group.
+///
+/// `activation` identifies the current activation (e.g. the start of the active window),
+/// so the group stays on the same wallpaper until it is activated again.
+/// If the last wallpaper was removed, continues with the next one in sorted order.
+pub fn sequential_index(
+    position: &mut SequentialPosition,
+    wallpapers: &[String],
+    activation: i64,
+) -> usize {
+    let last_index = position
+        .last
+        .as_ref()
+        .and_then(|last| wallpapers.iter().position(|wallpaper| wallpaper == last));
+
+    let index = match (last_index, &position.last) {
+        (Some(index), _) if position.activation == Some(activation) => index,
+        (Some(index), _) => (index + 1) % wallpapers.len().max(1),
+        (None, Some(last)) => wallpapers
+            .iter()
+            .position(|wallpaper| wallpaper > last)
+            .unwrap_or(0),
+        (None, None) => 0,
+    };
+
+    position.last = wallpapers.get(index).cloned();
+    position.activation = Some(activation);
+    index
+}
+
+/// Picks "today's" wallpaper, which stays the same for the whole day.
+///
+/// Seeded from the date, the group and an optional user seed, so the same inputs
+/// always yield the same wallpaper, across restarts and machines.
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use expression::core::selection::daily_index;
+///
+/// let group = vec!["a.jpg".to_string(), "b.jpg".to_string(), "c.jpg".to_string()];
+/// let today = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+///
+/// let index = daily_index(&group, today, "~/Pictures/07", "");
+/// assert_eq!(index, daily_index(&group, today, "~/Pictures/07", ""));
+/// ```
+pub fn daily_index(wallpapers: &[String], date: NaiveDate, group: &str, seed: &str) -> usize {
+    let input = format!("{}\0{}\0{}", date.format("%Y-%m-%d"), group, seed);
+    (stable_hash(input.as_bytes()) % wallpapers.len().max(1) as u64) as usize
+}
+
+/// FNV-1a hash of `bytes`, the same on every platform and Rust release
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Group key which is the same on machines with different home dirs (e.g. `~/Pictures/07`)
+pub fn portable_key(key: &str) -> String {
+    dirs::home_dir()
+        .and_then(|home| {
+            Path::new(key)
+                .strip_prefix(home)
+                .ok()
+                .map(Path::to_path_buf)
+        })
+        .map(|relative| format!("~/{}", relative.display()))
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// Weight of a wallpaper for the `weighted` strategy.
+///
+/// Taken from the group's `[weights]` by file name, then from an `@N` suffix
+/// in the file name (e.g. `aurora@5.jpg`), and 1 otherwise.
+///
+/// # Example
+/// ```
+/// use expression::core::selection::weight_of;
+/// use std::collections::BTreeMap;
+///
+/// let weights = BTreeMap::from([("aurora.jpg".to_string(), 5)]);
+///
+/// assert_eq!(weight_of("/group/aurora.jpg", &weights), 5);
+/// assert_eq!(weight_of("/group/comet@3.jpg", &weights), 3);
+/// assert_eq!(weight_of("/group/moon.jpg", &weights), 1);
+/// ```
+pub fn weight_of(wallpaper: &str, weights: &BTreeMap<String, u32>) -> u32 {
+    let path = Path::new(wallpaper);
+    let file_name = path.file_name().and_then(|name| name.to_str());
+    if let Some(weight) = file_name.and_then(|name| weights.get(name)) {
+        return *weight;
+    }
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.rsplit_once('@'))
+        .and_then(|(_, weight)| weight.parse().ok())
+        .unwrap_or(1)
+}
+
+/// Picks a random wallpaper, proportionally to its weight.
+/// Falls back to a uniform pick if every weight is 0.
+pub fn weighted_index(wallpapers: &[String], weights: &BTreeMap<String, u32>) -> usize {
+    let weights: Vec<u64> = wallpapers
+        .iter()
+        .map(|wallpaper| weight_of(wallpaper, weights) as u64)
+        .collect();
+    let total: u64 = weights.iter().sum();
+    if total == 0 {
+        return rand::random_range(0..wallpapers.len().max(1));
+    }
+
+    let mut roll = rand::random_range(0..total);
+    for (index, weight) in weights.iter().enumerate() {
+        if roll < *weight {
+            return index;
+        }
+        roll -= weight;
+    }
+    wallpapers.len() - 1
+}
@@ -0,0 +1,326 @@
+use crate::config::RandomScope;
+use log2::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    process, thread,
+    time::{Duration, SystemTime},
+};
+
+// How long to wait for another writer of the state file, and when its lock counts as stale
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+const LOCK_STALE: Duration = Duration::from_secs(10);
+
+/// Progress which should survive restarts (e.g. shuffle bags), stored as TOML.
+/// Unlike the config, this file is written by Expression itself.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    /// Shuffle bags by group key
+    #[serde(default)]
+    pub shuffle: BTreeMap<String, ShuffleBag>,
+
+    /// Sequential positions by group key
+    #[serde(default)]
+    pub sequential: BTreeMap<String, SequentialPosition>,
+
+    /// Collection chosen at runtime (`expression collection <name>`), wins over the config
+    pub collection: Option<String>,
+
+    /// Random scope chosen at runtime (`expression random <scope> [ttl]`), wins over the config
+    pub random: Option<RandomOverride>,
+
+    /// Focus session started at runtime (`expression session start`)
+    pub session: Option<Session>,
+
+    /// One-shot overrides added at runtime (`expression override add`)
+    #[serde(default)]
+    pub overrides: Vec<ScheduledOverride>,
+}
+
+/// Remaining permutation of a group for the `shuffle` strategy
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ShuffleBag {
+    /// Wallpapers not yet shown in this cycle, in order
+    #[serde(default)]
+    pub remaining: Vec<String>,
+    /// Last shown wallpaper, never shown first in the next cycle
+    pub last: Option<String>,
+}
+
+/// Position of a group for the `sequential` strategy
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SequentialPosition {
+    /// Last shown wallpaper
+    pub last: Option<String>,
+    /// Start (unix timestamp) of the activation the last wallpaper was shown for
+    pub activation: Option<i64>,
+}
+
+/// Temporary randomization, e.g. "surprise me for the next 2 hours"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RandomOverride {
+    pub scope: RandomScope,
+    /// End (unix timestamp), active until cleared otherwise
+    pub until: Option<i64>,
+}
+
+impl RandomOverride {
+    pub fn is_active(&self, timestamp: i64) -> bool {
+        self.until.is_none_or(|until| timestamp < until)
+    }
+}
+
+/// Timed session alternating between a focus and a break item, e.g. 4 rounds of 25m + 5m
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Start (unix timestamp)
+    pub start: i64,
+    pub rounds: u32,
+    /// Length of each focus phase in seconds
+    pub focus: u64,
+    /// Length of each break phase in seconds
+    #[serde(rename = "break")]
+    pub rest: u64,
+    /// Items in the special collection
+    pub focus_item: String,
+    pub break_item: String,
+}
+
+/// Part of a session round
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionPhase {
+    Focus,
+    Break,
+}
+
+impl fmt::Display for SessionPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionPhase::Focus => write!(f, "focus"),
+            SessionPhase::Break => write!(f, "break"),
+        }
+    }
+}
+
+impl Session {
+    /// Phase at a time, with its round (from 1), start and end (unix timestamps).
+    /// None before the session starts and after its last break.
+    ///
+    /// # Example
+    /// ```
+    /// use expression::core::state::{Session, SessionPhase};
+    ///
+    /// let session = Session {
+    ///     start: 0,
+    ///     rounds: 2,
+    ///     focus: 1500,
+    ///     rest: 300,
+    ///     focus_item: "focus".into(),
+    ///     break_item: "break".into(),
+    /// };
+    ///
+    /// assert_eq!(session.phase(1600), Some((SessionPhase::Break, 1, 1500, 1800)));
+    /// assert_eq!(session.phase(1800), Some((SessionPhase::Focus, 2, 1800, 3300)));
+    /// assert_eq!(session.phase(3600), None);
+    /// ```
+    pub fn phase(&self, timestamp: i64) -> Option<(SessionPhase, u32, i64, i64)> {
+        let round_length = (self.focus + self.rest) as i64;
+        if timestamp < self.start || timestamp >= self.end() || round_length == 0 {
+            return None;
+        }
+        let elapsed = timestamp - self.start;
+        let round_start = self.start + elapsed / round_length * round_length;
+        let round = (elapsed / round_length) as u32 + 1;
+        let focus_end = round_start + self.focus as i64;
+        match timestamp < focus_end {
+            true => Some((SessionPhase::Focus, round, round_start, focus_end)),
+            false => Some((
+                SessionPhase::Break,
+                round,
+                focus_end,
+                round_start + round_length,
+            )),
+        }
+    }
+
+    /// End of the last break (unix timestamp)
+    pub fn end(&self) -> i64 {
+        self.start + (self.focus + self.rest) as i64 * self.rounds as i64
+    }
+
+    pub fn item(&self, phase: SessionPhase) -> &str {
+        match phase {
+            SessionPhase::Focus => &self.focus_item,
+            SessionPhase::Break => &self.break_item,
+        }
+    }
+}
+
+/// A special item shown once for a while, e.g. `launch_day` on 2026-10-20 from 09:00 for 1h
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledOverride {
+    /// Number for cancelling, unique among the pending overrides
+    pub id: u32,
+    /// Item in the special collection
+    pub item: String,
+    /// Start and end (unix timestamps, end exclusive)
+    pub start: i64,
+    pub end: i64,
+}
+
+impl ScheduledOverride {
+    pub fn is_active(&self, timestamp: i64) -> bool {
+        (self.start..self.end).contains(&timestamp)
+    }
+
+    pub fn is_expired(&self, timestamp: i64) -> bool {
+        timestamp >= self.end
+    }
+}
+
+/// Default state file: `$XDG_STATE_HOME/expression/state.toml`
+/// (local data dir on platforms without a state dir)
+pub fn state_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|path| path.join("expression/state.toml"))
+}
+
+/// Last modification of the state file, to notice changes made by commands
+pub fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Exclusive access to the state file, released when dropped.
+/// The daemon and commands both take it around read, change and write.
+pub struct StateLock {
+    path: PathBuf,
+}
+
+impl Drop for StateLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Locks the state file at `path`, waiting for another writer to finish.
+/// A lock left behind by a crashed writer is taken over once it's stale.
+pub fn lock(path: &Path) -> Result<StateLock, Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let lock_path = path.with_extension("toml.lock");
+    let started = SystemTime::now();
+    loop {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+        {
+            Ok(_) => return Ok(StateLock { path: lock_path }),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                let is_stale = modified(&lock_path)
+                    .and_then(|modified| modified.elapsed().ok())
+                    .is_some_and(|age| age > LOCK_STALE);
+                if is_stale {
+                    warn!("Removing stale state lock {}", lock_path.display());
+                    let _ = fs::remove_file(&lock_path);
+                    continue;
+                }
+                if started.elapsed().unwrap_or_default() > LOCK_TIMEOUT {
+                    return Err(format!("State file is locked: {}", lock_path.display()).into());
+                }
+                thread::sleep(Duration::from_millis(20));
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+impl State {
+    /// Loads the state, starting fresh if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+        match fs::read_to_string(path).map(|content| toml::from_str(&content)) {
+            Ok(Ok(state)) => state,
+            Ok(Err(err)) => {
+                warn!("Ignoring invalid state file {}: {}", path.display(), err);
+                Self::default()
+            }
+            Err(err) => {
+                warn!("Failed to read state file {}: {}", path.display(), err);
+                Self::default()
+            }
+        }
+    }
+
+    /// Adds a one-shot override with the next free id, which is returned
+    pub fn add_override(&mut self, item: String, start: i64, end: i64) -> u32 {
+        let id = self
+            .overrides
+            .iter()
+            .map(|entry| entry.id)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        self.overrides.push(ScheduledOverride {
+            id,
+            item,
+            start,
+            end,
+        });
+        self.overrides.sort_by_key(|entry| (entry.start, entry.id));
+        id
+    }
+
+    /// Removes the overrides which are over, returns them
+    pub fn expire_overrides(&mut self, timestamp: i64) -> Vec<ScheduledOverride> {
+        let (expired, pending) = self
+            .overrides
+            .drain(..)
+            .partition(|entry| entry.is_expired(timestamp));
+        self.overrides = pending;
+        expired
+    }
+
+    /// Override showing at a time, the latest started one if several overlap
+    pub fn active_override(&self, timestamp: i64) -> Option<&ScheduledOverride> {
+        self.overrides
+            .iter()
+            .filter(|entry| entry.is_active(timestamp))
+            .max_by_key(|entry| (entry.start, entry.id))
+    }
+
+    /// Writes the state atomically. Use [`State::update`] (or hold a [`lock`])
+    /// so changes made by other processes in the meantime aren't lost.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write then rename, so a crash never leaves a half written state
+        let partial = path.with_extension(format!("toml.{}.part", process::id()));
+        fs::write(&partial, toml::to_string(self)?)?;
+        fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    /// Changes the state file under its lock: re-reads the latest state, applies `change`
+    /// and writes it back. Returns the result of `change`.
+    pub fn update<T>(
+        path: &Path,
+        change: impl FnOnce(&mut Self) -> T,
+    ) -> Result<T, Box<dyn Error>> {
+        let _lock = lock(path)?;
+        let mut state = Self::load(path);
+        let result = change(&mut state);
+        state.save(path)?;
+        Ok(result)
+    }
+}
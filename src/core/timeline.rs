@@ -0,0 +1,137 @@
+use crate::config::{GroupSelectionStrategy, TimelineConfig};
+use crate::core::wallpaper::{self, WallpaperItem};
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Lays a plain folder of images across the day, as if they were named `HH`.
+///
+/// Images are taken in natural order (`2.jpg` before `10.jpg`) or in the explicit `order`,
+/// then given even slots across the window, or the slot start hours from `slots`.
+/// Each image fills every hour of its slot. When there are more images than hours,
+/// images sharing an hour form a [`WallpaperItem::Sequence`], see [`sequence_strategy`].
+pub fn get_timeline_items(
+    wallpaper_dir: &str,
+    supported_extensions: &[&str],
+    config: &TimelineConfig,
+) -> Result<BTreeMap<u8, Vec<WallpaperItem>>, io::Error> {
+    let mut wallpapers = wallpaper::get_wallpapers(wallpaper_dir, supported_extensions)?;
+    let wallpapers = if config.order.is_empty() {
+        wallpapers.sort_by(|a, b| natural_cmp(&file_name(a), &file_name(b)));
+        wallpapers
+    } else {
+        config
+            .order
+            .iter()
+            .map(|name| {
+                wallpapers
+                    .iter()
+                    .find(|path| file_name(path) == *name)
+                    .cloned()
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::NotFound,
+                            format!("Timeline image not found in {}: {}", wallpaper_dir, name),
+                        )
+                    })
+            })
+            .collect::<Result<_, _>>()?
+    };
+
+    let window = config.window_hours() as usize;
+    let offsets: Vec<usize> = if config.slots.is_empty() {
+        (0..wallpapers.len())
+            .map(|index| index * window / wallpapers.len())
+            .collect()
+    } else if config.slots.len() == wallpapers.len() {
+        config
+            .slots
+            .iter()
+            .map(|slot| config.hour_offset(*slot) as usize)
+            .collect()
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Timeline has {} slots for {} images",
+                config.slots.len(),
+                wallpapers.len()
+            ),
+        ));
+    };
+
+    let mut wallpaper_map: BTreeMap<u8, Vec<WallpaperItem>> = BTreeMap::new();
+    let mut current: Option<WallpaperItem> = None;
+    for offset in 0..window {
+        let starting: Vec<PathBuf> = wallpapers
+            .iter()
+            .zip(&offsets)
+            .filter(|(_, start)| **start == offset)
+            .map(|(path, _)| path.clone())
+            .collect();
+        match starting.len() {
+            0 => {}
+            1 => current = Some(WallpaperItem::Entry(starting[0].clone())),
+            _ => current = Some(WallpaperItem::Sequence(starting)),
+        }
+        if let Some(item) = &current {
+            let hour = ((config.start as usize + offset) % 24) as u8;
+            wallpaper_map.insert(hour, vec![item.clone()]);
+        }
+    }
+
+    Ok(wallpaper_map)
+}
+
+/// Strategy of a timeline sequence, which is always spread in its order
+/// whatever the configured `group_selection_strategy`.
+/// Returns None for other items.
+pub fn sequence_strategy(item: &[WallpaperItem]) -> Option<GroupSelectionStrategy> {
+    matches!(item.first(), Some(WallpaperItem::Sequence(_)))
+        .then_some(GroupSelectionStrategy::Spread)
+}
+
+/// Compares names with digit runs as numbers, e.g. `img2` before `img10`
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(a_char), Some(b_char)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+        let ordering = if a_char.is_ascii_digit() && b_char.is_ascii_digit() {
+            let (a_digits, a_rest) = split_digits(a);
+            let (b_digits, b_rest) = split_digits(b);
+            (a, b) = (a_rest, b_rest);
+            let (a_digits, b_digits) = (
+                a_digits.trim_start_matches('0'),
+                b_digits.trim_start_matches('0'),
+            );
+            a_digits
+                .len()
+                .cmp(&b_digits.len())
+                .then_with(|| a_digits.cmp(b_digits))
+        } else {
+            (a, b) = (&a[a_char.len_utf8()..], &b[b_char.len_utf8()..]);
+            a_char.to_lowercase().cmp(b_char.to_lowercase())
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+fn split_digits(value: &str) -> (&str, &str) {
+    let end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    value.split_at(end)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -1,3 +1,5 @@
+use crate::config::GapPolicy;
+use crate::utils::solar::SolarAnchor;
 use colored::Colorize;
 use std::{
     collections::BTreeMap,
@@ -9,6 +11,8 @@ use std::{
 pub enum WallpaperItem {
     Entry(PathBuf),
     Group(PathBuf),
+    /// Ordered files sharing an hour, laid out by the timeline (always spread in order)
+    Sequence(Vec<PathBuf>),
 }
 
 /// Get all wallpapers with supported extensions in a directory
@@ -49,6 +53,7 @@ pub fn get_wallpapers(
 /// - Collections are directories which are not groups (HH)
 /// - So, any directories with names other than HH are considered collections
 /// - Collections e.g. `Dark_Mode/`, `Nature/`, `Light_Mode/`, etc
+/// - Solar groups (e.g. `sunset/`) are not collections either
 pub fn get_collections(wallpaper_dir: &str) -> Result<Vec<PathBuf>, io::Error> {
     let entries = fs::read_dir(wallpaper_dir)?;
     let mut directories = Vec::new();
@@ -60,6 +65,9 @@ pub fn get_collections(wallpaper_dir: &str) -> Result<Vec<PathBuf>, io::Error> {
         if path.is_dir() {
             let dir_name = path.file_name().and_then(|n| n.to_str());
             if let Some(name) = dir_name {
+                if SolarAnchor::parse(name).is_some() {
+                    continue;
+                }
                 if name.len() > 2 {
                     directories.push(path);
                 } else {
@@ -77,6 +85,29 @@ pub fn get_collections(wallpaper_dir: &str) -> Result<Vec<PathBuf>, io::Error> {
     Ok(directories)
 }
 
+/// Finds a collection by name (case insensitive) in a directory
+pub fn find_collection(collections_dir: &str, names: &[&str]) -> Option<PathBuf> {
+    get_collections(collections_dir)
+        .ok()?
+        .into_iter()
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+                .unwrap_or(false)
+        })
+}
+
+/// Overrides base items with collection items hour by hour.
+/// Hours missing in the collection fall back to the base items.
+pub fn overlay_items(
+    mut base: BTreeMap<u8, Vec<WallpaperItem>>,
+    overlay: BTreeMap<u8, Vec<WallpaperItem>>,
+) -> BTreeMap<u8, Vec<WallpaperItem>> {
+    base.extend(overlay);
+    base
+}
+
 /// Retrieves all wallpaper items (includes entries and groups) from a directory.
 ///
 /// Items must be formatted as `HH` (e.g., `05`, `12`, `23`).
@@ -90,6 +121,56 @@ pub fn get_wallpaper_items(
     time_filter: Option<u8>,
     // NOTE: Using BTreeMap instead of HashMap to auto sort entries by key
     // Sorting HashMap is less efficient
+) -> Result<BTreeMap<u8, Vec<WallpaperItem>>, io::Error> {
+    let mut wallpaper_map = collect_items(wallpaper_dir, supported_extensions, None)?;
+
+    // filter
+    if let Some(filter_hour) = time_filter {
+        wallpaper_map.retain(|&hour, _entry_vec| hour >= filter_hour);
+    }
+
+    if wallpaper_map.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No wallpaper entries found in: {}", wallpaper_dir),
+        ));
+    }
+
+    Ok(wallpaper_map)
+}
+
+/// Like [`get_wallpaper_items`], with the items tagged for a color scheme on top,
+/// e.g. `07.dark.jpg` or `20.light/` replace `07.jpg` and `20/` for that scheme.
+/// Items tagged for other schemes are ignored.
+pub fn get_scheme_items(
+    wallpaper_dir: &str,
+    supported_extensions: &[&str],
+    scheme: Option<&str>,
+) -> Result<BTreeMap<u8, Vec<WallpaperItem>>, io::Error> {
+    let mut wallpaper_map = collect_items(wallpaper_dir, supported_extensions, None)?;
+    if let Some(scheme) = scheme {
+        wallpaper_map.extend(collect_items(
+            wallpaper_dir,
+            supported_extensions,
+            Some(scheme),
+        )?);
+    }
+
+    if wallpaper_map.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No wallpaper entries found in: {}", wallpaper_dir),
+        ));
+    }
+
+    Ok(wallpaper_map)
+}
+
+/// Collects `HH` items, or `HH.tag` items if a tag is given
+fn collect_items(
+    wallpaper_dir: &str,
+    supported_extensions: &[&str],
+    tag: Option<&str>,
 ) -> Result<BTreeMap<u8, Vec<WallpaperItem>>, io::Error> {
     let entries = fs::read_dir(wallpaper_dir)?;
     let mut wallpaper_map: BTreeMap<u8, Vec<WallpaperItem>> = BTreeMap::new();
@@ -97,10 +178,25 @@ pub fn get_wallpaper_items(
     for entry in entries.flatten() {
         // NOTE: .flatten() auto skips failed results
         let path = entry.path();
-        let filename = path.file_stem().and_then(|name| name.to_str());
-
-        if let Some(filename) = filename {
-            if let Ok(hour) = filename.parse::<u8>() {
+        // Whole name for dirs, so `07.dark/` is not taken for `07/`
+        let filename = if path.is_dir() {
+            path.file_name()
+        } else {
+            path.file_stem()
+        };
+        let filename = filename.and_then(|name| name.to_str());
+
+        let hour_name = match (filename, tag) {
+            (Some(filename), None) => Some(filename),
+            (Some(filename), Some(tag)) => filename
+                .split_once('.')
+                .filter(|(_, name_tag)| name_tag.eq_ignore_ascii_case(tag))
+                .map(|(hour, _)| hour),
+            (None, _) => None,
+        };
+
+        if let Some(hour_name) = hour_name {
+            if let Ok(hour) = hour_name.parse::<u8>() {
                 // Invalid Entry/Group name
                 if hour >= 24 {
                     continue;
@@ -122,7 +218,7 @@ pub fn get_wallpaper_items(
                 };
 
                 // BTreeMap: dir b4 file
-                let list = wallpaper_map.entry(hour).or_insert_with(Vec::new);
+                let list = wallpaper_map.entry(hour).or_default();
                 if matches!(entry_type, WallpaperItem::Group(_)) {
                     list.insert(0, entry_type); // Push directory to front
                 } else {
@@ -132,19 +228,99 @@ pub fn get_wallpaper_items(
         }
     }
 
-    // filter
-    if let Some(filter_hour) = time_filter {
-        wallpaper_map.retain(|&hour, _entry_vec| hour >= filter_hour);
+    Ok(wallpaper_map)
+}
+
+/// Resolves which hour's item to use for the given hour.
+///
+/// Returns the hour itself if it has an item, otherwise the gap policy decides:
+/// - `hold`: last earlier hour, wrapping across midnight (e.g. 23 for 02 if nothing earlier)
+/// - `next`: next later hour, wrapping across midnight
+/// - `nearest`: closest hour in either direction, earlier one on ties
+/// - `random`: any hour with an item
+/// - `none`: no item
+pub fn resolve_gap_hour(
+    items: &BTreeMap<u8, Vec<WallpaperItem>>,
+    hour: u8,
+    policy: &GapPolicy,
+) -> Option<u8> {
+    if items.contains_key(&hour) {
+        return Some(hour);
+    }
+    let previous = || items.range(..hour).next_back().or(items.iter().next_back());
+    let next = || items.range(hour..).next().or(items.iter().next());
+    match policy {
+        GapPolicy::Hold => previous().map(|(h, _)| *h),
+        GapPolicy::Next => next().map(|(h, _)| *h),
+        GapPolicy::Nearest => {
+            let distance = |from: u8, to: u8| (to as i16 - from as i16).rem_euclid(24);
+            let (previous, next) = (previous()?.0, next()?.0);
+            if distance(hour, *next) < distance(*previous, hour) {
+                Some(*next)
+            } else {
+                Some(*previous)
+            }
+        }
+        GapPolicy::Random => {
+            let hours: Vec<&u8> = items.keys().collect();
+            if hours.is_empty() {
+                return None;
+            }
+            Some(*hours[rand::random_range(0..hours.len())])
+        }
+        GapPolicy::None => None,
     }
+}
 
-    if wallpaper_map.is_empty() {
-        return Err(io::Error::new(
-            io::ErrorKind::NotFound,
-            format!("No wallpaper entries found in: {}", wallpaper_dir),
-        ));
+/// Resolves which hour's item the gap layer shows for the given hour.
+///
+/// Same as [`resolve_gap_hour`], except for the `none` policy: an item which changes over its span
+/// (a group or sequence, or any item while `crossfade` is on) still lasts until the next item,
+/// while a plain entry is left as it is.
+pub fn resolve_fill_hour(
+    items: &BTreeMap<u8, Vec<WallpaperItem>>,
+    hour: u8,
+    policy: &GapPolicy,
+    crossfade: bool,
+) -> Option<u8> {
+    match policy {
+        GapPolicy::None => resolve_gap_hour(items, hour, &GapPolicy::Hold).filter(|item_hour| {
+            crossfade || !matches!(items[item_hour].first(), Some(WallpaperItem::Entry(_)))
+        }),
+        policy => resolve_gap_hour(items, hour, policy),
     }
+}
 
-    Ok(wallpaper_map)
+/// Counts the contiguous hours before and after `hour` which resolve to the same item.
+///
+/// Together with `hour` itself, this is the span during which the item stays active.
+/// e.g. with items at 06, 12 and 20 and the `hold` policy, hour 14 returns `(2, 5)` (12:00 to 20:00).
+/// With the `none` policy nothing replaces an item until the next one, so it spans like `hold`.
+/// Random gaps can't be predicted, so they only span their own hour.
+pub fn resolve_gap_span(
+    items: &BTreeMap<u8, Vec<WallpaperItem>>,
+    hour: u8,
+    policy: &GapPolicy,
+) -> (u8, u8) {
+    let policy = match policy {
+        GapPolicy::Random => return (0, 0),
+        GapPolicy::None => &GapPolicy::Hold,
+        policy => policy,
+    };
+    let Some(item_hour) = resolve_gap_hour(items, hour, policy) else {
+        return (0, 0);
+    };
+    let resolves_to_item = |offset: i16| {
+        let other = (hour as i16 + offset).rem_euclid(24) as u8;
+        resolve_gap_hour(items, other, policy) == Some(item_hour)
+    };
+    let before = (1..24i16)
+        .take_while(|offset| resolves_to_item(-offset))
+        .count() as u8;
+    let after = (1..24 - before as i16)
+        .take_while(|offset| resolves_to_item(*offset))
+        .count() as u8;
+    (before, after)
 }
 
 /// Retrieves all special items (both entries and groups) from a directory.
@@ -184,7 +360,7 @@ pub fn get_special_items(
             };
 
             // BTreeMap: dir b4 file
-            let list = wallpaper_map.entry(filename).or_insert_with(Vec::new);
+            let list = wallpaper_map.entry(filename).or_default();
             if matches!(entry_type, WallpaperItem::Group(_)) {
                 list.insert(0, entry_type); // Push directory to front
             } else {
@@ -203,6 +379,24 @@ pub fn get_special_items(
     Ok(wallpaper_map)
 }
 
+/// Retrieves all solar items (both entries and groups) from a directory.
+///
+/// Items must be named after a solar anchor (e.g. `sunrise.jpg`, `sunset-30m/`).
+/// Returns an empty list if there are no solar items.
+pub fn get_solar_items(
+    wallpaper_dir: &str,
+    supported_extensions: &[&str],
+) -> Vec<(SolarAnchor, Vec<WallpaperItem>)> {
+    get_special_items(wallpaper_dir, supported_extensions)
+        .map(|items| {
+            items
+                .into_iter()
+                .filter_map(|(name, item)| SolarAnchor::parse(&name).map(|anchor| (anchor, item)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 // █▀ █▀▀ █░░ █▀▀ █▀▀ ▀█▀ █ █▀█ █▄░█
 // ▄█ ██▄ █▄▄ ██▄ █▄▄ ░█░ █ █▄█ █░▀█
 
@@ -210,9 +404,8 @@ use log2::info;
 
 /// Selects a random wallpaper from a wallpaper Group.
 /// Returns a tuple of (path, index, total)
-/// WARN: Deprecated for now. Will be useful in the future
-/// upon implementing global/collection temporary randomization scope
-pub fn select_random_entry(path: &PathBuf, extensions: &[&str]) -> Option<(String, usize, usize)> {
+/// For randomizing across hours and collections, see [`random_pool`]
+pub fn select_random_entry(path: &Path, extensions: &[&str]) -> Option<(String, usize, usize)> {
     let sub_collection_dir = path.display().to_string();
     let sub_entries = get_wallpapers(&sub_collection_dir, extensions).ok()?;
     if sub_entries.is_empty() {
@@ -225,6 +418,50 @@ pub fn select_random_entry(path: &PathBuf, extensions: &[&str]) -> Option<(Strin
     Some((selected_wallpaper, wallpaper_index, sub_entries.len()))
 }
 
+/// Collects every wallpaper of the given hour (or of all hours) into one sorted pool.
+/// Items are resolved like [`select_wallpaper_item`]: a group wins over an entry of the same hour.
+pub fn random_pool(
+    item_maps: &[BTreeMap<u8, Vec<WallpaperItem>>],
+    hour: Option<u8>,
+    extensions: &[&str],
+) -> Vec<String> {
+    let mut pool: Vec<String> = item_maps
+        .iter()
+        .flat_map(|items| {
+            items
+                .iter()
+                .filter(|(item_hour, _)| hour.is_none_or(|hour| **item_hour == hour))
+                .filter_map(|(_, item)| item.first())
+        })
+        .flat_map(|item| match item {
+            WallpaperItem::Entry(path) => vec![path.clone()],
+            WallpaperItem::Group(path) => {
+                get_wallpapers(&path.display().to_string(), extensions).unwrap_or_default()
+            }
+            WallpaperItem::Sequence(paths) => paths.clone(),
+        })
+        .map(|path| path.display().to_string())
+        .collect();
+    pool.sort();
+    pool.dedup();
+    pool
+}
+
+/// Identifies a wallpaper item across runs (e.g. for persisted selection progress).
+/// Uses the path of the item which would be selected.
+pub fn item_key(entry_vector: &[WallpaperItem]) -> String {
+    match entry_vector.first() {
+        Some(WallpaperItem::Entry(path)) | Some(WallpaperItem::Group(path)) => {
+            path.display().to_string()
+        }
+        Some(WallpaperItem::Sequence(paths)) => paths
+            .first()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default(),
+        None => String::new(),
+    }
+}
+
 /// Selects a Wallpaper Item from Wallpaper Object (entry/group)
 /// Returns a vector of wallpaper paths inside the item
 /// Note: Groups always have higher priority over Entry by default
@@ -236,7 +473,7 @@ pub fn select_wallpaper_item(
         match entry {
             WallpaperItem::Group(path) => {
                 let group_dir = path.display().to_string();
-                if let Some(wallpapers) = get_wallpapers(&group_dir, extensions).ok() {
+                if let Ok(wallpapers) = get_wallpapers(&group_dir, extensions) {
                     let mut wallpaper_vec: Vec<String> = Vec::new();
                     for wallpaper in wallpapers {
                         wallpaper_vec.push(wallpaper.display().to_string());
@@ -265,6 +502,17 @@ pub fn select_wallpaper_item(
                 info!("Selected Wallpaper: {}", file_name.bright_green());
                 return vec![entry_str];
             }
+
+            WallpaperItem::Sequence(paths) => {
+                info!(
+                    "Selected Sequence: {}",
+                    format!("{} wallpapers", paths.len()).bright_green()
+                );
+                return paths
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect();
+            }
         }
     }
     Vec::new()
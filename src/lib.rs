@@ -2,3 +2,4 @@ pub mod backends;
 pub mod utils;
 pub mod config;
 pub mod core;
+pub mod cli;
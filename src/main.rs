@@ -1,21 +1,31 @@
-use chrono::{Local, Timelike};
+use chrono::{DateTime, TimeDelta, TimeZone, Timelike};
 use colored::Colorize;
-use ctrlc;
 use expression::{
     backends::get_backend,
-    config::{get_group_config, Config, GroupSelectionStrategy},
-    core::wallpaper,
-    utils::{calc, cmd, logger},
+    cli,
+    config::{get_group_config, Config, GroupConfig, GroupSelectionStrategy, Layer, RandomScope},
+    core::{
+        calendar::{self, CalendarFile},
+        crossfade, rotation, rules, schedule, selection,
+        state::{self, State},
+        timeline,
+        wallpaper::{self, WallpaperItem},
+    },
+    utils::{appearance, calc, cmd, logger, season, solar, zone::Zone},
 };
 use log2::{debug, error, info, warn};
+use std::cell::Cell;
+use std::collections::BTreeMap;
 use std::process;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 const DEFAULT_INTERVAL_MINUTES: f64 = 60.0;
 const SECONDS_PER_HOUR: u64 = 3600;
 const SECONDS_PER_MINUTE: u64 = 60;
+// Rules are re-checked this often while waiting (probes run as often)
+const RULE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
 
 pub enum WaitStrategy {
     Sleep,   // Sleeps once for the entire interval
@@ -26,6 +36,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let start = Instant::now();
     let config = Config::load()?;
 
+    // Commands for the running daemon (e.g. `expression collection Nature`)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return cli::run(cli::Command::parse(&args)?, &config);
+    }
+
     let _log2 = logger::init();
     info!("----------------------------------");
 
@@ -41,14 +57,87 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // SETUP: Config Variables
     let wallpaper_dir = config.directories.wallpaper.as_str();
+    let collections_dir = config.directories.collections.as_str();
     let special_dir = config.directories.special;
-    let config_special_entries = config.special_entries;
+    let config_special_schedule = config.special_schedule;
     let config_special_enabled = config.general.enable_special;
+    let (hourly_specials, dated_specials): (Vec<_>, Vec<_>) = config_special_schedule
+        .iter()
+        .cloned()
+        .partition(|entry| entry.precedence() == schedule::Precedence::Hourly);
+    let config_layers = config.general.layers;
+    let layer_position = |wanted| config_layers.iter().position(|layer| *layer == wanted);
+    // Root items win over collections on shared hours only when listed first
+    let is_root_first = matches!(
+        (layer_position(Layer::Root), layer_position(Layer::Collection)),
+        (Some(root), Some(collection)) if root < collection
+    );
+    // Layers left out don't reach the gap layer either
+    let has_root = layer_position(Layer::Root).is_some();
+    let has_collection = layer_position(Layer::Collection).is_some();
+    let config_location = config.location;
+    let config_zone = config.zone;
+    let config_seasons = config.seasons;
+    let config_timeline = config.timeline;
+    let config_crossfade = config.crossfade;
+    let crossfade_step = config_crossfade.step_seconds().unwrap_or(300);
+    let crossfade_cache = crossfade::cache_dir();
+    let state_path = state::state_path();
+    let config_astronomy = config.astronomy_schedule;
     let config_group_strategy = config.general.group_selection_strategy;
+    let config_gap_policy = config.general.gap_policy;
+    let config_daily_seed = config.general.daily_seed;
+    let config_collection = config.general.collection;
+    let config_collection_schedule = config.collection_schedule;
+    let config_random_scope = config.general.random_scope;
+    let config_rules = config.rule_set;
+    let config_probes = config.probes;
+    let config_appearance = config.appearance;
+    let config_calendar = config.calendar;
+    let mut calendar_file = config_calendar
+        .file
+        .as_ref()
+        .filter(|_| config_calendar.enabled)
+        .map(CalendarFile::new);
+    let scheme_file = config_appearance
+        .file
+        .as_ref()
+        .map(std::path::PathBuf::from);
     let exec_cmd = config.general.execute_on_change;
 
     let mut selected_item = Vec::new();
 
+    debug!(
+        "Layers: {}",
+        config_layers
+            .iter()
+            .map(Layer::to_string)
+            .collect::<Vec<_>>()
+            .join(" > ")
+    );
+
+    if config_zone != Zone::Local {
+        info!("Time Zone: {}", config_zone.to_string().bright_yellow());
+    }
+
+    // SETUP: Color Scheme Watcher (switches as soon as the preference changes)
+    let scheme_changed = Arc::new(AtomicBool::new(false));
+    let mut scheme_source = None;
+    if config_appearance.enabled {
+        match appearance::detect(config_appearance.source, scheme_file.as_deref()) {
+            Some((source, scheme)) => {
+                info!(
+                    "Color Scheme: {} (from {:?})",
+                    scheme.to_string().bright_yellow(),
+                    source
+                );
+                appearance::watch(source, scheme_file.clone(), scheme_changed.clone());
+                scheme_source = Some(source);
+            }
+            None => warn!("No color scheme source available, ignoring [appearance]"),
+        }
+    }
+
     // SETUP: Signal Handler
     let running = Arc::new(AtomicBool::new(true));
     let r = running.clone();
@@ -62,7 +151,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // MAIN: Main Loop
     while running.load(Ordering::SeqCst) {
         let start = Instant::now();
-        let now = Local::now();
+        let now = config_zone.now();
         let hour = now.hour() as u8;
         info!(
             "Current Time: {}",
@@ -70,60 +159,575 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         );
 
         let mut current_strategy = config_group_strategy.clone();
+        let mut selected_key = String::new();
+        let mut group_config = None;
+
+        // Start and end of the selected item's active window (for spreading groups)
+        let hour_start = now - TimeDelta::seconds((now.minute() * 60 + now.second()) as i64);
+        let mut active_window: Option<(DateTime<Zone>, DateTime<Zone>)> = None;
+        let mut crossfade_pair = None;
+        let mut is_special_active = false;
 
         // █▀▀ █▀█ █░░ █░░ █▀▀ █▀▀ ▀█▀ █ █▀█ █▄░█
         // █▄▄ █▄█ █▄▄ █▄▄ ██▄ █▄▄ ░█░ █ █▄█ █░▀█
         // Capture Current Collection Content
 
-        // TODO: Collection: Theme Override Strategy
-        /* let collections = wallpaper::get_collections(wallpaper_dir)?;
-        for collection in collections {
-            println!("collection: {}", collection.display());
-        } */
-
-        // TODO: Randomized Scope Strategy
-
-        // COLLECTION: Special Collection
-        if selected_item.is_empty() && config_special_enabled {
-            match wallpaper::get_special_items(&special_dir, extensions) {
-                Ok(special_items) => {
-                    if let Some(filename) = config_special_entries.get(&hour.to_string()) {
-                        if let Some(item) = special_items.get(filename) {
-                            selected_item = wallpaper::select_wallpaper_item(item, extensions);
-                            info!("Special Collection Activated!");
-
-                            // Local Group Config Overrides
-                            if let Some(local_config) = get_group_config(item) {
-                                if let Some(local_general) = local_config.general {
-                                    if let Some(local_strategy) = local_general.selection_strategy {
-                                        current_strategy = local_strategy;
-                                        debug!("Using Group specific Config overrides");
-                                    }
-                                }
+        // Color Scheme (items tagged e.g. `07.dark.jpg` win for the current scheme)
+        let color_scheme =
+            scheme_source.and_then(|source| appearance::read(source, scheme_file.as_deref()));
+        let scheme_tag = color_scheme.map(|scheme| scheme.name());
+
+        // Runtime choices (commands) win over config
+        let runtime_state = state_path.as_deref().map(State::load).unwrap_or_default();
+
+        // One-shot Overrides (dropped from the state file once they're over)
+        if let (Some(path), true) = (
+            state_path.as_deref(),
+            runtime_state
+                .overrides
+                .iter()
+                .any(|entry| entry.is_expired(now.timestamp())),
+        ) {
+            match State::update(path, |state| state.expire_overrides(now.timestamp())) {
+                Ok(expired) => {
+                    for entry in expired {
+                        info!("Override Expired: #{} {}", entry.id, entry.item);
+                    }
+                }
+                Err(err) => warn!("Failed to remove expired overrides: {}", err),
+            }
+        }
+        let active_override = runtime_state
+            .active_override(now.timestamp())
+            .and_then(|entry| {
+                let start = config_zone.timestamp_opt(entry.start, 0).single()?;
+                let end = config_zone.timestamp_opt(entry.end, 0).single()?;
+                Some((entry.clone(), start, end))
+            });
+        let next_override = runtime_state
+            .overrides
+            .iter()
+            .map(|entry| entry.start)
+            .filter(|start| *start > now.timestamp())
+            .min()
+            .and_then(|start| config_zone.timestamp_opt(start, 0).single());
+
+        // Active Collection (runtime, then calendar schedule, then config)
+        let today = now.date_naive();
+        let scheduled_collection =
+            rotation::scheduled_collection(&config_collection_schedule, today);
+        let collection_switch = rotation::next_switch(&config_collection_schedule, today);
+        if let (None, Some(collection)) = (&runtime_state.collection, scheduled_collection) {
+            info!(
+                "Scheduled Collection: {} (until {})",
+                collection.bright_yellow(),
+                collection_switch.map_or("further notice".to_string(), |(date, _)| {
+                    date.format("%Y-%m-%d").to_string()
+                })
+            );
+        }
+        let active_collection = runtime_state
+            .collection
+            .or_else(|| scheduled_collection.map(String::from))
+            .or_else(|| config_collection.clone());
+
+        // Randomized Scope (runtime choice falls back to config once it expires)
+        let runtime_random = runtime_state
+            .random
+            .filter(|random| random.is_active(now.timestamp()));
+        let random_until = runtime_random
+            .as_ref()
+            .and_then(|random| random.until)
+            .and_then(|until| config_zone.timestamp_opt(until, 0).single());
+        let random_scope = runtime_random
+            .map(|random| random.scope)
+            .or(config_random_scope);
+
+        // Focus Session (runtime only, ends by itself after its last break)
+        let session_phase = runtime_state.session.as_ref().and_then(|session| {
+            let (phase, round, start, end) = session.phase(now.timestamp())?;
+            let start = config_zone.timestamp_opt(start, 0).single()?;
+            let end = config_zone.timestamp_opt(end, 0).single()?;
+            Some((session, phase, round, start, end))
+        });
+
+        // Calendar Events (re-read whenever the file changes)
+        if let Some(calendar_file) = &mut calendar_file {
+            match calendar_file.refresh() {
+                Ok(true) => info!(
+                    "Calendar Loaded: {} ({} events)",
+                    calendar_file.path().display(),
+                    calendar_file.events.len()
+                ),
+                Ok(false) => {}
+                Err(err) => error!(
+                    "Calendar Error ({}): {}",
+                    calendar_file.path().display(),
+                    err
+                ),
+            }
+        }
+        let calendar_events = calendar_file
+            .as_ref()
+            .map_or(&[][..], |calendar_file| &calendar_file.events[..]);
+
+        // Rules are matched up front, a change wakes the loop below
+        let variables = rules::SystemVariables::new(now, &config_probes);
+        let matched_rule = rules::first_match(&config_rules, &|name| variables.get(name));
+
+        // COLLECTION: Collection Overrides (loaded once a normal layer is reached)
+        let load_collection_items = || {
+            let mut items = BTreeMap::new();
+
+            // Season Collection Overrides
+            if config_seasons.enabled {
+                let season =
+                    season::get_season(now, &config_seasons.boundaries, &config_seasons.hemisphere);
+                match wallpaper::find_collection(collections_dir, season.collection_names()) {
+                    Some(collection) => {
+                        let collection_dir = collection.to_string_lossy();
+                        match wallpaper::get_scheme_items(&collection_dir, extensions, scheme_tag) {
+                            Ok(season_items) => {
+                                info!(
+                                    "Season Collection Activated: {}",
+                                    season.to_string().bright_yellow()
+                                );
+                                items = wallpaper::overlay_items(items, season_items);
                             }
+                            Err(err) => warn!("Season Collection Error: {}", err),
                         }
                     }
+                    None => debug!("No collection for season: {}", season),
+                }
+            }
+
+            // Astronomy Collection Overrides (later conditions win on shared hours)
+            for (condition, collection) in &config_astronomy {
+                if !condition.is_met(now.date_naive()) {
+                    continue;
+                }
+                match wallpaper::find_collection(collections_dir, &[collection]) {
+                    Some(collection) => {
+                        let collection_dir = collection.to_string_lossy();
+                        match wallpaper::get_scheme_items(&collection_dir, extensions, scheme_tag) {
+                            Ok(astro_items) => {
+                                info!(
+                                    "Astronomy Collection Activated: {}",
+                                    condition.to_string().bright_yellow()
+                                );
+                                items = wallpaper::overlay_items(items, astro_items);
+                            }
+                            Err(err) => warn!("Astronomy Collection Error: {}", err),
+                        }
+                    }
+                    None => warn!("Collection not found for {}: {}", condition, collection),
+                }
+            }
+
+            // Color Scheme Collection Overrides
+            if let Some(scheme) = color_scheme {
+                let name = config_appearance.collection(scheme);
+                match wallpaper::find_collection(collections_dir, &[name]) {
+                    Some(collection) => {
+                        let collection_dir = collection.to_string_lossy();
+                        match wallpaper::get_scheme_items(&collection_dir, extensions, scheme_tag) {
+                            Ok(scheme_items) => {
+                                info!(
+                                    "Color Scheme Collection Activated: {}",
+                                    scheme.to_string().bright_yellow()
+                                );
+                                items = wallpaper::overlay_items(items, scheme_items);
+                            }
+                            Err(err) => warn!("Color Scheme Collection Error: {}", err),
+                        }
+                    }
+                    None => debug!("No collection for color scheme: {}", scheme),
                 }
-                Err(err) => error!("Special Collection Error: {}", err),
             }
-        }
 
-        // COLLECTION: Normal Collection
-        if selected_item.is_empty() {
+            // Active Collection Overrides (an explicit choice wins on shared hours)
+            if let Some(name) = &active_collection {
+                match wallpaper::find_collection(collections_dir, &[name]) {
+                    Some(collection) => {
+                        let collection_dir = collection.to_string_lossy();
+                        match wallpaper::get_scheme_items(&collection_dir, extensions, scheme_tag) {
+                            Ok(collection_items) => {
+                                info!("Collection Activated: {}", name.bright_yellow());
+                                items = wallpaper::overlay_items(items, collection_items);
+                            }
+                            Err(err) => warn!("Collection Error: {}", err),
+                        }
+                    }
+                    None => warn!("Collection not found: {}", name),
+                }
+            }
+            items
+        };
+
+        // COLLECTION: Solar Items
+        let solar_items = match config_location {
+            Some(_) => wallpaper::get_solar_items(wallpaper_dir, extensions),
+            None => Vec::new(),
+        };
+
+        // Collection, root and both combined, for the normal layers and random scopes
+        let load_normal_items = || -> Result<_, Box<dyn std::error::Error>> {
             // Since this is the most important feature of all, propogate error and break if it fails
-            let items = wallpaper::get_wallpaper_items(wallpaper_dir, extensions, Some(hour))?;
-            if let Some(item) = items.get(&hour) {
-                selected_item = wallpaper::select_wallpaper_item(item, extensions);
-
-                // Local Group Config Overrides
-                if let Some(local_config) = get_group_config(item) {
-                    if let Some(local_general) = local_config.general {
-                        if let Some(local_strategy) = local_general.selection_strategy {
-                            current_strategy = local_strategy;
-                            debug!("Using Group specific Config overrides");
+            let root_items = if !has_root {
+                BTreeMap::new()
+            } else if config_timeline.enabled {
+                timeline::get_timeline_items(wallpaper_dir, extensions, &config_timeline)?
+            } else {
+                wallpaper::get_scheme_items(wallpaper_dir, extensions, scheme_tag)?
+            };
+            let collection_items = if has_collection {
+                load_collection_items()
+            } else {
+                BTreeMap::new()
+            };
+            // Combined in layer order, for gaps, spans and crossfades
+            let items = if is_root_first {
+                wallpaper::overlay_items(collection_items.clone(), root_items.clone())
+            } else {
+                wallpaper::overlay_items(root_items.clone(), collection_items.clone())
+            };
+            Ok((collection_items, root_items, items))
+        };
+        let mut normal_items = None;
+        let mut winning_layer = None;
+
+        // Layers in configured priority, the first one with an item wins
+        for layer in &config_layers {
+            let reason = match layer {
+                // LAYER: Focus Session (focus or break item of the current phase)
+                Layer::Session => {
+                    let Some((session, phase, round, start, end)) = &session_phase else {
+                        continue;
+                    };
+                    let name = session.item(*phase);
+                    let Some(special) =
+                        select_special_item(&special_dir, extensions, name, "Session")
+                    else {
+                        continue;
+                    };
+                    (selected_item, selected_key, group_config) = special;
+                    active_window = Some((*start, *end));
+                    is_special_active = true;
+                    info!(
+                        "Session Activated! ({} {}/{} until {})",
+                        phase.to_string().bright_yellow(),
+                        round,
+                        session.rounds,
+                        end.format("%H:%M:%S")
+                    );
+                    format!("session is in {} {}/{}", phase, round, session.rounds)
+                }
+
+                // LAYER: One-shot Overrides (latest started override)
+                Layer::Override => {
+                    let Some((entry, start, end)) = &active_override else {
+                        continue;
+                    };
+                    let Some(special) =
+                        select_special_item(&special_dir, extensions, &entry.item, "Override")
+                    else {
+                        continue;
+                    };
+                    (selected_item, selected_key, group_config) = special;
+                    active_window = Some((*start, *end));
+                    is_special_active = true;
+                    info!(
+                        "Override Activated! (#{} {} until {})",
+                        entry.id,
+                        entry.item.bright_yellow(),
+                        end.format("%H:%M:%S")
+                    );
+                    format!("override #{} runs until {}", entry.id, end.format("%H:%M"))
+                }
+
+                // LAYER: Rules (first matching rule)
+                Layer::Rules => {
+                    let Some(rule) = matched_rule else { continue };
+                    let Some(special) =
+                        select_special_item(&special_dir, extensions, &rule.item, "Rule")
+                    else {
+                        continue;
+                    };
+                    (selected_item, selected_key, group_config) = special;
+                    is_special_active = true;
+                    info!("Rule Matched: {}", rule.to_string().bright_yellow());
+                    format!("rule `{}` matched", rule)
+                }
+
+                // LAYER: Special Collection (dated, then hourly entries by default)
+                Layer::Dated | Layer::Hourly => {
+                    if !config_special_enabled {
+                        continue;
+                    }
+                    let entries = match layer {
+                        Layer::Dated => &dated_specials,
+                        _ => &hourly_specials,
+                    };
+                    let Some(entry) =
+                        schedule::resolve_special(entries, now, config_location.as_ref())
+                    else {
+                        continue;
+                    };
+                    let special_items = match wallpaper::get_special_items(&special_dir, extensions)
+                    {
+                        Ok(special_items) => special_items,
+                        Err(err) => {
+                            error!("Special Collection Error: {}", err);
+                            continue;
                         }
+                    };
+                    let Some(item) = special_items.get(&entry.item) else {
+                        continue;
+                    };
+                    selected_item = wallpaper::select_wallpaper_item(item, extensions);
+                    selected_key = wallpaper::item_key(item);
+                    active_window = entry.active_window(now, config_location.as_ref());
+                    is_special_active = true;
+                    info!(
+                        "Special Collection Activated! ({} entry: {})",
+                        entry.precedence(),
+                        entry.key.bright_yellow()
+                    );
+                    group_config = get_group_config(item);
+                    format!("{} entry `{}` is active", entry.precedence(), entry.key)
+                }
+
+                // LAYER: Calendar Events (latest started event with an item)
+                Layer::Calendar => {
+                    let active_events = calendar::active_events(calendar_events, now);
+                    if active_events.is_empty() {
+                        continue;
+                    }
+                    let Ok(special_items) = wallpaper::get_special_items(&special_dir, extensions)
+                    else {
+                        continue;
+                    };
+                    let active_event = active_events.into_iter().find_map(|(event, start, end)| {
+                        config_calendar
+                            .items(event)
+                            .into_iter()
+                            .find_map(|name| special_items.get(name))
+                            .map(|item| (event, item, start, end))
+                    });
+                    let Some((event, item, start, end)) = active_event else {
+                        debug!("No special item for the active calendar events");
+                        continue;
+                    };
+                    selected_item = wallpaper::select_wallpaper_item(item, extensions);
+                    selected_key = wallpaper::item_key(item);
+                    active_window = Some((start, end));
+                    is_special_active = true;
+                    info!(
+                        "Calendar Event Activated! ({} until {})",
+                        event.summary.bright_yellow(),
+                        end.format("%Y-%m-%d %H:%M")
+                    );
+                    group_config = get_group_config(item);
+                    format!("calendar event `{}` is on", event.summary)
+                }
+
+                // LAYER: Solar Items (latest started slot)
+                Layer::Solar => {
+                    let Some(location) = &config_location else {
+                        continue;
+                    };
+                    let active_solar_item = solar_items
+                        .iter()
+                        .filter_map(|(anchor, item)| {
+                            solar::slot_start(anchor, now, location, solar::SLOT_SECONDS)
+                                .map(|start| (start, anchor, item))
+                        })
+                        .max_by_key(|(start, _, _)| *start);
+                    let Some((start, anchor, item)) = active_solar_item else {
+                        continue;
+                    };
+                    selected_item = wallpaper::select_wallpaper_item(item, extensions);
+                    selected_key = wallpaper::item_key(item);
+                    active_window = Some((start, start + TimeDelta::seconds(solar::SLOT_SECONDS)));
+                    info!(
+                        "Solar Item Activated! ({} at {})",
+                        anchor.to_string().bright_yellow(),
+                        start.format("%H:%M:%S")
+                    );
+                    group_config = get_group_config(item);
+                    format!("{} slot started at {}", anchor, start.format("%H:%M:%S"))
+                }
+
+                // LAYER: Normal Collection (collection, root, gap)
+                Layer::Collection | Layer::Root | Layer::Gap => {
+                    if normal_items.is_none() {
+                        normal_items = Some(load_normal_items()?);
+                    }
+                    let Some((collection_items, root_items, items)) = &normal_items else {
+                        continue;
+                    };
+
+                    let (item_hour, reason) = match layer {
+                        Layer::Collection => (
+                            collection_items.contains_key(&hour).then_some(hour),
+                            format!("a collection has an item for {:02}", hour),
+                        ),
+                        Layer::Root => (
+                            root_items.contains_key(&hour).then_some(hour),
+                            format!("the root has an item for {:02}", hour),
+                        ),
+                        _ => {
+                            let gap_hour = wallpaper::resolve_fill_hour(
+                                items,
+                                hour,
+                                &config_gap_policy,
+                                config_crossfade.enabled,
+                            )
+                            .filter(|_| !items.contains_key(&hour));
+                            (
+                                gap_hour,
+                                format!(
+                                    "no item for {:02}, filled with {:02} ({:?})",
+                                    hour,
+                                    gap_hour.unwrap_or_default(),
+                                    config_gap_policy
+                                ),
+                            )
+                        }
+                    };
+                    let Some(item_hour) = item_hour else {
+                        continue;
+                    };
+                    if item_hour != hour {
+                        info!(
+                            "No item for {}, filling gap with {} ({:?})",
+                            hour,
+                            format!("{:02}", item_hour).bright_yellow(),
+                            config_gap_policy
+                        );
+                    }
+                    let item = match layer {
+                        Layer::Collection => &collection_items[&item_hour],
+                        Layer::Root => &root_items[&item_hour],
+                        _ => &items[&item_hour],
+                    };
+                    selected_item = wallpaper::select_wallpaper_item(item, extensions);
+                    selected_key = wallpaper::item_key(item);
+
+                    // Active from the item's slot until the next defined item
+                    let (before, after) =
+                        wallpaper::resolve_gap_span(items, hour, &config_gap_policy);
+                    let mut window_end = hour_start + TimeDelta::hours(after as i64 + 1);
+                    if let Some(location) = &config_location {
+                        let anchors: Vec<_> =
+                            solar_items.iter().map(|(anchor, _)| *anchor).collect();
+                        let next_solar =
+                            solar::next_boundary(&anchors, now, location, solar::SLOT_SECONDS);
+                        window_end = next_solar.map_or(window_end, |next| next.min(window_end));
+                    }
+                    let window_start = hour_start - TimeDelta::hours(before as i64);
+                    active_window = Some((window_start, window_end));
+
+                    // Crossfade towards the next item, over the whole (unclipped) span
+                    let next_hour = (hour + after + 1) % 24;
+                    let next_item =
+                        wallpaper::resolve_gap_hour(items, next_hour, &config_gap_policy)
+                            .and_then(|next_hour| items[&next_hour].first());
+                    if let (
+                        true,
+                        Some(WallpaperItem::Entry(from)),
+                        Some(WallpaperItem::Entry(to)),
+                    ) = (config_crossfade.enabled, item.first(), next_item)
+                    {
+                        if from != to {
+                            let fade_end = hour_start + TimeDelta::hours(after as i64 + 1);
+                            crossfade_pair =
+                                Some((from.clone(), to.clone(), window_start, fade_end));
+                        }
+                    }
+                    group_config = get_group_config(item);
+                    // Timeline sequences keep their order, whatever the strategy
+                    if let Some(strategy) = timeline::sequence_strategy(item) {
+                        current_strategy = strategy;
                     }
+                    reason
                 }
+            };
+
+            // Local Group Config Overrides
+            if let Some(local_strategy) = get_group_strategy(group_config.as_ref()) {
+                current_strategy = local_strategy;
+            }
+            debug!(
+                "Layer won: {} ({})",
+                layer.to_string().bright_yellow(),
+                reason
+            );
+            winning_layer = Some(*layer);
+            break;
+        }
+
+        // Randomized Scope Overrides (replaces the item of the normal layers)
+        let is_normal_winner = matches!(
+            winning_layer,
+            None | Some(Layer::Collection | Layer::Root | Layer::Gap)
+        );
+        if let (Some(scope), true) = (random_scope, is_normal_winner) {
+            // Active items, even if no normal layer was reached
+            let active_items = match normal_items {
+                Some((_, _, items)) => items,
+                None => load_normal_items()?.2,
+            };
+            let mut item_maps = vec![active_items];
+            if scope == RandomScope::All {
+                let other_dirs = wallpaper::get_collections(collections_dir)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|collection| *collection != std::path::Path::new(&special_dir));
+                for dir in std::iter::once(wallpaper_dir.into()).chain(other_dirs) {
+                    let dir = dir.to_string_lossy();
+                    if let Ok(other_items) =
+                        wallpaper::get_scheme_items(&dir, extensions, scheme_tag)
+                    {
+                        item_maps.push(other_items);
+                    }
+                }
+            }
+            let hour_filter = (scope == RandomScope::Hour).then_some(hour);
+            let pool = wallpaper::random_pool(&item_maps, hour_filter, extensions);
+            if pool.is_empty() {
+                warn!("No wallpaper in random scope: {}", scope);
+            } else {
+                info!(
+                    "Random Scope Activated: {} ({} wallpapers)",
+                    scope.to_string().bright_yellow(),
+                    pool.len()
+                );
+                selected_item = pool;
+                selected_key.clear();
+                group_config = None;
+                current_strategy = GroupSelectionStrategy::Random;
+                active_window = None;
+                crossfade_pair = None;
+            }
+        }
+
+        // Active window ends early if a special entry takes over
+        if let (Some((_, window_end)), false, true) = (
+            &mut active_window,
+            is_special_active,
+            config_special_enabled,
+        ) {
+            let next_special =
+                schedule::next_activation(&config_special_schedule, now, config_location.as_ref());
+            let next_event = calendar::next_change(calendar_events, now);
+            let next_special = next_special
+                .into_iter()
+                .chain(next_event)
+                .chain(next_override)
+                .min();
+            if let Some(next_special) = next_special {
+                *window_end = next_special.min(*window_end);
             }
         }
 
@@ -135,18 +739,63 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         debug!("Exec Time: {}", format!("{:?}", start.elapsed()).cyan());
         let start = Instant::now();
 
-        let mut interval = DEFAULT_INTERVAL_MINUTES;
+        // Local Group Rotation Interval (only strategies which pick anew on each wake)
+        let group_interval = group_config
+            .as_ref()
+            .and_then(|config| config.general.as_ref()?.interval_minutes)
+            .filter(|minutes| *minutes > 0)
+            .filter(|_| {
+                matches!(
+                    current_strategy,
+                    GroupSelectionStrategy::Random
+                        | GroupSelectionStrategy::Shuffle
+                        | GroupSelectionStrategy::Weighted
+                )
+            });
+        let interval = group_interval.map_or(DEFAULT_INTERVAL_MINUTES, |minutes| minutes as f64);
         let mut refresh_strategy = WaitStrategy::Refresh;
+        let mut slice_wait = None;
 
         let item_size = selected_item.len();
         if item_size == 0 {
             warn!("No wallpaper available for {}", hour);
         } else if item_size == 1 {
             // SELECT: Fixed Time Strategy
-            backend.apply_wallpaper(&selected_item[0])?;
+            let mut wallpaper = selected_item[0].clone();
+
+            // SELECT: Crossfade Frame
+            if let (Some((from, to, fade_start, fade_end)), Some(cache)) =
+                (&crossfade_pair, &crossfade_cache)
+            {
+                let span = (*fade_end - *fade_start).num_seconds() as u64;
+                let steps = span.div_ceil(crossfade_step).max(1) as usize;
+                let (frame_index, frame_wait) =
+                    calc::get_span_index(*fade_start, *fade_end, now, steps);
+                if frame_index > 0 {
+                    match crossfade::get_frame(cache, from, to, frame_index, steps) {
+                        Ok(frame) => {
+                            info!(
+                                "Crossfade frame [{}/{}] towards: {}",
+                                frame_index.to_string().cyan(),
+                                steps.to_string().cyan(),
+                                to.file_name()
+                                    .and_then(|name| name.to_str())
+                                    .unwrap_or("Unknown")
+                                    .bright_green()
+                            );
+                            wallpaper = frame.display().to_string();
+                        }
+                        Err(err) => warn!("Crossfade Error: {}", err),
+                    }
+                }
+                slice_wait = Some(frame_wait);
+                refresh_strategy = WaitStrategy::Sleep;
+            }
+
+            backend.apply_wallpaper(&wallpaper)?;
             info!(
                 "Wallpaper applied: {}",
-                std::path::Path::new(&selected_item[0])
+                std::path::Path::new(&wallpaper)
                     .file_name()
                     .and_then(|name| name.to_str())
                     .unwrap_or("Unknown")
@@ -156,14 +805,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             match current_strategy {
                 GroupSelectionStrategy::Spread => {
                     let total_items = selected_item.len();
-                    let max_spread_items = interval as usize;
-                    if total_items > max_spread_items {
-                        warn!("Too many wallpapers to spread effectively ({total_items} > {max_spread_items})");
-                    }
-                    let slice_duration = interval / total_items as f64;
-                    let wallpaper_index = calc::get_group_index(now, total_items);
+                    let (window_start, window_end) =
+                        active_window.unwrap_or((hour_start, hour_start + TimeDelta::hours(1)));
+                    debug!(
+                        "Spreading group from {} to {}",
+                        window_start.format("%H:%M:%S"),
+                        window_end.format("%H:%M:%S")
+                    );
+                    let (wallpaper_index, spread_slice_wait) =
+                        calc::get_span_index(window_start, window_end, now, total_items);
 
-                    backend.apply_wallpaper(&selected_item[wallpaper_index as usize])?;
+                    backend.apply_wallpaper(&selected_item[wallpaper_index])?;
                     info!(
                         "Wallpaper (group) applied [{}/{}]: {}",
                         (wallpaper_index + 1).to_string().cyan(),
@@ -175,9 +827,111 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             .bright_green()
                     );
                     // Overrides
-                    interval = slice_duration;
+                    slice_wait = Some(spread_slice_wait);
                     refresh_strategy = WaitStrategy::Sleep;
                 }
+                GroupSelectionStrategy::Shuffle => {
+                    let pick = |state: &mut State| {
+                        let bag = state.shuffle.entry(selected_key.clone()).or_default();
+                        (
+                            selection::shuffle_index(bag, &selected_item),
+                            bag.remaining.len(),
+                        )
+                    };
+                    let (wallpaper_index, remaining) = match &state_path {
+                        Some(path) => State::update(path, pick).unwrap_or_else(|err| {
+                            warn!("Failed to save shuffle progress: {}", err);
+                            pick(&mut State::default())
+                        }),
+                        None => {
+                            warn!("No state directory, shuffle progress is not persisted");
+                            pick(&mut State::default())
+                        }
+                    };
+
+                    backend.apply_wallpaper(&selected_item[wallpaper_index])?;
+                    info!(
+                        "Wallpaper (shuffle) applied [{} left in cycle]: {}",
+                        remaining.to_string().cyan(),
+                        std::path::Path::new(&selected_item[wallpaper_index])
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .unwrap_or("Unknown")
+                            .bright_green()
+                    );
+                }
+                GroupSelectionStrategy::Sequential => {
+                    let (activation, _) =
+                        active_window.unwrap_or((hour_start, hour_start + TimeDelta::hours(1)));
+                    let pick = |state: &mut State| {
+                        let position = state.sequential.entry(selected_key.clone()).or_default();
+                        selection::sequential_index(
+                            position,
+                            &selected_item,
+                            activation.timestamp(),
+                        )
+                    };
+                    let wallpaper_index = match &state_path {
+                        Some(path) => State::update(path, pick).unwrap_or_else(|err| {
+                            warn!("Failed to save sequential progress: {}", err);
+                            pick(&mut State::default())
+                        }),
+                        None => {
+                            warn!("No state directory, sequential progress is not persisted");
+                            pick(&mut State::default())
+                        }
+                    };
+
+                    backend.apply_wallpaper(&selected_item[wallpaper_index])?;
+                    info!(
+                        "Wallpaper (sequential) applied [{}/{}]: {}",
+                        (wallpaper_index + 1).to_string().cyan(),
+                        item_size.to_string().cyan(),
+                        std::path::Path::new(&selected_item[wallpaper_index])
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .unwrap_or("Unknown")
+                            .bright_green()
+                    );
+                }
+                GroupSelectionStrategy::Daily => {
+                    let wallpaper_index = selection::daily_index(
+                        &selected_item,
+                        now.date_naive(),
+                        &selection::portable_key(&selected_key),
+                        &config_daily_seed,
+                    );
+                    backend.apply_wallpaper(&selected_item[wallpaper_index])?;
+                    info!(
+                        "Wallpaper (daily) applied [{}/{}]: {}",
+                        (wallpaper_index + 1).to_string().cyan(),
+                        item_size.to_string().cyan(),
+                        std::path::Path::new(&selected_item[wallpaper_index])
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .unwrap_or("Unknown")
+                            .bright_green()
+                    );
+                }
+                GroupSelectionStrategy::Weighted => {
+                    let weights = group_config
+                        .as_ref()
+                        .map(|config| config.weights.clone())
+                        .unwrap_or_default();
+                    let wallpaper_index = selection::weighted_index(&selected_item, &weights);
+                    backend.apply_wallpaper(&selected_item[wallpaper_index])?;
+                    info!(
+                        "Wallpaper (weighted) applied [weight {}]: {}",
+                        selection::weight_of(&selected_item[wallpaper_index], &weights)
+                            .to_string()
+                            .cyan(),
+                        std::path::Path::new(&selected_item[wallpaper_index])
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .unwrap_or("Unknown")
+                            .bright_green()
+                    );
+                }
                 GroupSelectionStrategy::Random => {
                     info!("Multiple wallpapers available for {}", hour);
                     let wallpaper_index = rand::random_range(0..item_size);
@@ -206,7 +960,67 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Wistful Wake Wait Workflow
 
         // Wait: 24 Hour Cycle Strategy
-        let wait_seconds = calc::wait_time(interval, now);
+        let mut wait_seconds = calc::wait_time(interval, now);
+        let mut is_interval_wait = true;
+
+        // Wait: Group Rotation, never past the group's active window
+        if group_interval.is_some() {
+            let (_, window_end) =
+                active_window.unwrap_or((hour_start, hour_start + TimeDelta::hours(1)));
+            let rotation_wait = calc::rotation_wait(interval, now, window_end);
+            if rotation_wait < wait_seconds {
+                debug!("Group active until {}", window_end.format("%H:%M:%S"));
+                wait_seconds = rotation_wait;
+                is_interval_wait = false;
+            }
+        }
+
+        // Wait: Spread/Crossfade Strategy
+        if let Some(slice_wait) = slice_wait {
+            wait_seconds = slice_wait;
+            is_interval_wait = false;
+        }
+
+        // Wait: Scheduled Event Strategy (solar, cron, ranges)
+        let mut next_event = None;
+        if config_special_enabled {
+            next_event =
+                schedule::next_boundary(&config_special_schedule, now, config_location.as_ref());
+        }
+        if let Some(location) = &config_location {
+            let anchors: Vec<_> = solar_items.iter().map(|(anchor, _)| *anchor).collect();
+            let next_solar = solar::next_boundary(&anchors, now, location, solar::SLOT_SECONDS);
+            next_event = next_event.into_iter().chain(next_solar).min();
+        }
+        if let Some((date, _)) = collection_switch {
+            let switch = date
+                .and_hms_opt(0, 0, 0)
+                .and_then(|midnight| config_zone.from_local_datetime(&midnight).earliest());
+            next_event = next_event.into_iter().chain(switch).min();
+        }
+        if let Some(next_calendar) = calendar::next_change(calendar_events, now) {
+            next_event = next_event.into_iter().chain(Some(next_calendar)).min();
+        }
+        if let Some((_, _, end)) = active_override {
+            next_event = next_event.into_iter().chain(Some(end)).min();
+        }
+        next_event = next_event.into_iter().chain(next_override).min();
+        if let Some((_, _, _, _, end)) = session_phase {
+            next_event = next_event.into_iter().chain(Some(end)).min();
+        }
+        if let Some(until) = random_until {
+            debug!("Random scope until {}", until.format("%H:%M:%S"));
+            next_event = next_event.into_iter().chain(Some(until)).min();
+        }
+        if let Some(next) = next_event {
+            let event_wait = (next - now).num_seconds().max(1) as u64;
+            if event_wait < wait_seconds {
+                debug!("Next scheduled event at {}", next.format("%H:%M:%S"));
+                wait_seconds = event_wait;
+                is_interval_wait = false;
+            }
+        }
+
         info!(
             "Waiting for: {}",
             format!(
@@ -221,8 +1035,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         selected_item.clear();
 
         // EXECUTE SCRIPT
-        if exec_cmd.is_some() {
-            let result = cmd::execute(exec_cmd.as_ref().unwrap());
+        if let Some(exec_cmd) = &exec_cmd {
+            let result = cmd::execute(exec_cmd);
             match result {
                 Ok(_) => {}
                 Err(err) => error!("Error executing command: {}", err),
@@ -233,7 +1047,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         let full_wait_secs = (now.minute() * 60 + now.second()) + wait_seconds as u32;
         let expected_secs = (interval * 60.0).ceil() as u32;
         let diff = full_wait_secs % expected_secs;
-        if diff != 0 {
+        if diff != 0 && is_interval_wait {
             warn!(
                 "Wait time misaligned by {} seconds ({} != {})",
                 diff.to_string().red(),
@@ -242,11 +1056,63 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             );
         }
 
-        // REFRESH LOOP
+        // REFRESH LOOP (wake up early for commands, color scheme, rule and calendar changes)
+        let state_modified = state_path.as_deref().and_then(state::modified);
+        let matched_rule = matched_rule.map(|rule| rule.name.clone());
+        let last_rule_check = Cell::new(Instant::now());
+        let is_rule_changed = || {
+            if config_rules.is_empty() || last_rule_check.get().elapsed() < RULE_CHECK_INTERVAL {
+                return false;
+            }
+            last_rule_check.set(Instant::now());
+            let variables = rules::SystemVariables::new(config_zone.now(), &config_probes);
+            let rule = rules::first_match(&config_rules, &|name| variables.get(name));
+            rule.map(|rule| &rule.name) != matched_rule.as_ref()
+        };
+        let should_wake = || {
+            state_path.as_deref().and_then(state::modified) != state_modified
+                || scheme_changed.swap(false, Ordering::SeqCst)
+                || is_rule_changed()
+                || calendar_file.as_ref().is_some_and(CalendarFile::is_changed)
+        };
         match refresh_strategy {
-            WaitStrategy::Sleep => calc::sleep(wait_seconds),
-            WaitStrategy::Refresh => calc::refresh(interval, now, wait_seconds),
+            WaitStrategy::Sleep => {
+                calc::sleep_or_wake(wait_seconds, &should_wake);
+            }
+            WaitStrategy::Refresh => calc::refresh(interval, now, wait_seconds, &should_wake),
         }
     }
     Ok(())
 }
+
+/// Returns the selection strategy override from the group's local config.toml, if any
+/// Selects a special item by name for the layers outside the schedule (session, override, rules).
+/// Returns the selected wallpapers, the item key and its group config,
+/// or None (with a warning naming the `source`) if the item doesn't exist.
+fn select_special_item(
+    special_dir: &str,
+    extensions: &[&str],
+    name: &str,
+    source: &str,
+) -> Option<(Vec<String>, String, Option<GroupConfig>)> {
+    let special_items = wallpaper::get_special_items(special_dir, extensions);
+    let Some(item) = special_items
+        .as_ref()
+        .ok()
+        .and_then(|items| items.get(name))
+    else {
+        warn!("{} item not found in {}: {}", source, special_dir, name);
+        return None;
+    };
+    Some((
+        wallpaper::select_wallpaper_item(item, extensions),
+        wallpaper::item_key(item),
+        get_group_config(item),
+    ))
+}
+
+fn get_group_strategy(group_config: Option<&GroupConfig>) -> Option<GroupSelectionStrategy> {
+    let local_strategy = group_config?.general.as_ref()?.selection_strategy.clone()?;
+    debug!("Using Group specific Config overrides");
+    Some(local_strategy)
+}
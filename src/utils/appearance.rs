@@ -0,0 +1,196 @@
+use crate::config::SchemeSource;
+use log2::{debug, warn};
+use std::{
+    fmt, fs,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+// How often a scheme file is checked for changes
+const FILE_CHECK_SECONDS: u64 = 2;
+
+const PORTAL_DEST: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+
+/// Desktop color scheme preference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+impl ColorScheme {
+    /// Parses gsettings and scheme file values.
+    /// `default` (no preference) counts as light, like most desktops render it.
+    ///
+    /// # Example
+    /// ```
+    /// use expression::utils::appearance::ColorScheme;
+    ///
+    /// assert_eq!(ColorScheme::parse("'prefer-dark'"), Some(ColorScheme::Dark));
+    /// assert_eq!(ColorScheme::parse("light\n"), Some(ColorScheme::Light));
+    /// assert_eq!(ColorScheme::parse("solarized"), None);
+    /// ```
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().trim_matches('\'').to_lowercase().as_str() {
+            "dark" | "prefer-dark" => Some(ColorScheme::Dark),
+            "light" | "prefer-light" | "default" => Some(ColorScheme::Light),
+            _ => None,
+        }
+    }
+
+    /// Parses the portal's `color-scheme` reply, e.g. `(<<uint32 1>>,)`.
+    /// 1 is dark, 2 is light and 0 is no preference (light).
+    pub fn from_portal(reply: &str) -> Option<Self> {
+        let (_, value) = reply.rsplit_once("uint32 ")?;
+        let value: String = value.chars().take_while(char::is_ascii_digit).collect();
+        match value.parse::<u32>().ok()? {
+            1 => Some(ColorScheme::Dark),
+            0 | 2 => Some(ColorScheme::Light),
+            _ => None,
+        }
+    }
+
+    /// Tag of items and default collection name, e.g. `07.dark.jpg` and `dark/`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorScheme::Light => "light",
+            ColorScheme::Dark => "dark",
+        }
+    }
+}
+
+impl fmt::Display for ColorScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Finds the first source which can be read, in the order file, portal, gsettings for `auto`
+pub fn detect(source: SchemeSource, file: Option<&Path>) -> Option<(SchemeSource, ColorScheme)> {
+    let candidates: &[SchemeSource] = match source {
+        SchemeSource::Auto if file.is_some() => &[SchemeSource::File],
+        SchemeSource::Auto => &[SchemeSource::Portal, SchemeSource::Gsettings],
+        _ => std::slice::from_ref(&source),
+    };
+    candidates
+        .iter()
+        .find_map(|source| read(*source, file).map(|scheme| (*source, scheme)))
+}
+
+/// Reads the current preference from a source
+pub fn read(source: SchemeSource, file: Option<&Path>) -> Option<ColorScheme> {
+    match source {
+        SchemeSource::Auto => detect(source, file).map(|(_, scheme)| scheme),
+        SchemeSource::Portal => {
+            let reply = command_output(
+                "gdbus",
+                &[
+                    "call",
+                    "--session",
+                    "--dest",
+                    PORTAL_DEST,
+                    "--object-path",
+                    PORTAL_PATH,
+                    "--method",
+                    "org.freedesktop.portal.Settings.Read",
+                    "org.freedesktop.appearance",
+                    "color-scheme",
+                ],
+            )?;
+            ColorScheme::from_portal(&reply)
+        }
+        SchemeSource::Gsettings => {
+            let value = command_output(
+                "gsettings",
+                &["get", "org.gnome.desktop.interface", "color-scheme"],
+            )?;
+            ColorScheme::parse(&value)
+        }
+        SchemeSource::File => ColorScheme::parse(&fs::read_to_string(file?).ok()?),
+    }
+}
+
+/// Sets `changed` from a background thread whenever the preference may have changed.
+/// The portal and gsettings send change signals, a file is checked every few seconds.
+pub fn watch(source: SchemeSource, file: Option<PathBuf>, changed: Arc<AtomicBool>) {
+    thread::spawn(move || match source {
+        SchemeSource::File | SchemeSource::Auto => {
+            let Some(file) = file else { return };
+            let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+            let mut last = modified(&file);
+            loop {
+                thread::sleep(Duration::from_secs(FILE_CHECK_SECONDS));
+                let current = modified(&file);
+                if current != last {
+                    last = current;
+                    changed.store(true, Ordering::SeqCst);
+                }
+            }
+        }
+        SchemeSource::Portal => monitor(
+            "gdbus",
+            &[
+                "monitor",
+                "--session",
+                "--dest",
+                PORTAL_DEST,
+                "--object-path",
+                PORTAL_PATH,
+            ],
+            &changed,
+        ),
+        SchemeSource::Gsettings => monitor(
+            "gsettings",
+            &["monitor", "org.gnome.desktop.interface", "color-scheme"],
+            &changed,
+        ),
+    });
+}
+
+/// Runs a monitor command, flagging a change on every line mentioning the color scheme
+fn monitor(program: &str, args: &[&str], changed: &AtomicBool) {
+    let child = Command::new(program)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let stdout = match child {
+        Ok(mut child) => child.stdout.take(),
+        Err(err) => {
+            warn!("Failed to watch color scheme ({}): {}", program, err);
+            return;
+        }
+    };
+    for line in stdout
+        .map(BufReader::new)
+        .into_iter()
+        .flat_map(|out| out.lines())
+    {
+        let Ok(line) = line else { break };
+        if line.contains("color-scheme") {
+            debug!("Color scheme changed");
+            changed.store(true, Ordering::SeqCst);
+        }
+    }
+    warn!("Stopped watching color scheme ({})", program);
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,184 @@
+use super::solar;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use std::fmt;
+
+// Mean lunar phase from a reference new moon (2000-01-06 18:14 UTC)
+// Off by up to a day from the true phase, which is plenty for day scoped conditions
+const SYNODIC_MONTH_DAYS: f64 = 29.530588853;
+const REFERENCE_NEW_MOON: i64 = 947182440;
+
+// Days around new and full moon which still count as new and full (1/16 of the cycle)
+const PHASE_MARGIN_DAYS: f64 = SYNODIC_MONTH_DAYS / 16.0;
+
+// Peak dates of the major annual meteor showers (month, day)
+const METEOR_SHOWERS: [(&str, u32, u32); 10] = [
+    ("quadrantids", 1, 3),
+    ("lyrids", 4, 22),
+    ("eta_aquariids", 5, 6),
+    ("delta_aquariids", 7, 30),
+    ("perseids", 8, 12),
+    ("draconids", 10, 8),
+    ("orionids", 10, 21),
+    ("leonids", 11, 17),
+    ("geminids", 12, 14),
+    ("ursids", 12, 22),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoonPhase {
+    New,
+    Waxing,
+    Full,
+    Waning,
+}
+
+impl MoonPhase {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "new" => Some(Self::New),
+            "waxing" => Some(Self::Waxing),
+            "full" => Some(Self::Full),
+            "waning" => Some(Self::Waning),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Self::New => "new",
+            Self::Waxing => "waxing",
+            Self::Full => "full",
+            Self::Waning => "waning",
+        }
+    }
+}
+
+/// Astronomical condition which holds for whole days
+#[derive(Debug, Clone, PartialEq)]
+pub enum AstroCondition {
+    /// `moon:new`, `moon:waxing`, `moon:full` or `moon:waning`
+    Moon(MoonPhase),
+    /// `solstice`: day of the June or December solstice
+    Solstice,
+    /// `equinox`: day of the March or September equinox
+    Equinox,
+    /// `meteor_shower` for any peak, or a specific one like `meteor:perseids`
+    MeteorShower(Option<String>),
+}
+
+impl AstroCondition {
+    /// Parses a condition name.
+    /// Returns None if the value is not an astronomical condition.
+    pub fn parse(value: &str) -> Option<Result<Self, String>> {
+        let condition = match value {
+            "solstice" => Ok(Self::Solstice),
+            "equinox" => Ok(Self::Equinox),
+            "meteor_shower" => Ok(Self::MeteorShower(None)),
+            _ => {
+                if let Some(phase) = value.strip_prefix("moon:") {
+                    MoonPhase::from_name(phase)
+                        .map(Self::Moon)
+                        .ok_or_else(|| format!("unknown moon phase `{}`", phase))
+                } else if let Some(shower) = value.strip_prefix("meteor:") {
+                    METEOR_SHOWERS
+                        .iter()
+                        .find(|(name, _, _)| *name == shower)
+                        .map(|(name, _, _)| Self::MeteorShower(Some(name.to_string())))
+                        .ok_or_else(|| format!("unknown meteor shower `{}`", shower))
+                } else {
+                    return None;
+                }
+            }
+        };
+        Some(condition)
+    }
+
+    /// Checks if the condition holds on the given date
+    pub fn is_met(&self, date: NaiveDate) -> bool {
+        match self {
+            Self::Moon(phase) => date
+                .and_hms_opt(12, 0, 0)
+                .map(|noon| get_moon_phase(noon.and_utc()) == *phase)
+                .unwrap_or(false),
+            Self::Solstice => crosses_ecliptic_longitude(date, &[90.0, 270.0]),
+            Self::Equinox => crosses_ecliptic_longitude(date, &[0.0, 180.0]),
+            Self::MeteorShower(shower) => METEOR_SHOWERS.iter().any(|(name, month, day)| {
+                (date.month(), date.day()) == (*month, *day)
+                    && shower.as_ref().is_none_or(|shower| shower == name)
+            }),
+        }
+    }
+
+    /// Typical number of consecutive days the condition holds (narrower is more specific)
+    pub fn span_days(&self) -> i64 {
+        match self {
+            Self::Moon(MoonPhase::New | MoonPhase::Full) => 4,
+            Self::Moon(_) => 11,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for AstroCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Moon(phase) => write!(f, "moon:{}", phase.name()),
+            Self::Solstice => write!(f, "solstice"),
+            Self::Equinox => write!(f, "equinox"),
+            Self::MeteorShower(None) => write!(f, "meteor_shower"),
+            Self::MeteorShower(Some(shower)) => write!(f, "meteor:{}", shower),
+        }
+    }
+}
+
+/// Age of the moon in days since the last new moon (0 to 29.53)
+pub fn get_moon_age(time: DateTime<Utc>) -> f64 {
+    let days = (time.timestamp() - REFERENCE_NEW_MOON) as f64 / 86400.0;
+    days.rem_euclid(SYNODIC_MONTH_DAYS)
+}
+
+/// Determines the lunar phase at the given time.
+///
+/// # Example
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use expression::utils::astro::{get_moon_phase, MoonPhase};
+///
+/// let full_moon = Utc.with_ymd_and_hms(2025, 1, 13, 22, 0, 0).unwrap();
+///
+/// assert_eq!(get_moon_phase(full_moon), MoonPhase::Full);
+/// ```
+pub fn get_moon_phase(time: DateTime<Utc>) -> MoonPhase {
+    let age = get_moon_age(time);
+    let half = SYNODIC_MONTH_DAYS / 2.0;
+    if !(PHASE_MARGIN_DAYS..=SYNODIC_MONTH_DAYS - PHASE_MARGIN_DAYS).contains(&age) {
+        MoonPhase::New
+    } else if (age - half).abs() < PHASE_MARGIN_DAYS {
+        MoonPhase::Full
+    } else if age < half {
+        MoonPhase::Waxing
+    } else {
+        MoonPhase::Waning
+    }
+}
+
+/// Checks if the sun passes any of the given ecliptic longitudes during the (UTC) day
+fn crosses_ecliptic_longitude(date: NaiveDate, longitudes: &[f64]) -> bool {
+    let (Some(start), Some(end)) = (
+        date.and_hms_opt(0, 0, 0),
+        date.succ_opt().and_then(|next| next.and_hms_opt(0, 0, 0)),
+    ) else {
+        return false;
+    };
+    let start = solar::ecliptic_longitude(start.and_utc());
+    let mut end = solar::ecliptic_longitude(end.and_utc());
+    // Wraps from 360 to 0 at the March equinox
+    if end < start {
+        end += 360.0;
+    }
+    longitudes.iter().any(|longitude| {
+        [*longitude, longitude + 360.0]
+            .iter()
+            .any(|l| start < *l && *l <= end)
+    })
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Local, Timelike};
+use chrono::{DateTime, TimeDelta, TimeZone, Timelike, Utc};
 
 /// Calculates the current wallpaper group index based on the current time.
 ///
@@ -14,13 +14,54 @@ use chrono::{DateTime, Local, Timelike};
 /// let index = get_group_index(now, 19);
 /// println!("Current group: {}", index);
 /// ```
-pub fn get_group_index(now: DateTime<Local>, total_groups: usize) -> usize {
+pub fn get_group_index<Tz: TimeZone>(now: DateTime<Tz>, total_groups: usize) -> usize {
     let seconds = now.minute() * 60 + now.second();
     let total_slots = 60 * 60; // number of seconds in an hour
     let interval = total_slots as f64 / total_groups as f64;
     ((seconds as f64 / interval).floor() as usize).min(total_groups - 1)
 }
 
+/// Calculates the wallpaper group index for an arbitrary active window.
+///
+/// The window from `start` to `end` is divided evenly (at second precision) across
+/// the total number of wallpapers in the group.
+///
+/// # Returns
+/// A tuple:
+/// 1. `index` – Index of the wallpaper for the current time.
+/// 2. `wait_seconds` – Number of seconds until the next wallpaper (or the end of the window).
+///
+/// # Examples
+/// ```rust
+/// use chrono::{Local, TimeZone};
+/// use expression::utils::calc::get_span_index;
+///
+/// // 8 hour window with 4 wallpapers: one every 2 hours
+/// let start = Local.with_ymd_and_hms(2025, 3, 31, 22, 0, 0).unwrap();
+/// let end = Local.with_ymd_and_hms(2025, 4, 1, 6, 0, 0).unwrap();
+/// let now = Local.with_ymd_and_hms(2025, 4, 1, 1, 0, 0).unwrap();
+///
+/// let (index, wait_seconds) = get_span_index(start, end, now, 4);
+///
+/// assert_eq!(index, 1);
+/// assert_eq!(wait_seconds, 3600); // Next wallpaper at 02:00
+/// ```
+pub fn get_span_index<Tz: TimeZone<Offset: Copy>>(
+    start: DateTime<Tz>,
+    end: DateTime<Tz>,
+    now: DateTime<Tz>,
+    total_items: usize,
+) -> (usize, u64) {
+    let span = (end - start).num_seconds().max(1) as f64;
+    let elapsed = (now - start).num_seconds().clamp(0, span as i64) as f64;
+    let slice = span / total_items as f64;
+    let index = ((elapsed / slice).floor() as usize).min(total_items - 1);
+
+    let next_change = start + TimeDelta::seconds(((index + 1) as f64 * slice).ceil() as i64);
+    let wait = (next_change.min(end) - now).num_seconds().max(1);
+    (index, wait as u64)
+}
+
 /// Calculates how many seconds to wait until the next wallpaper refresh.
 ///
 /// The calculation is based on the given interval (in minutes) and the current time.
@@ -46,7 +87,7 @@ pub fn get_group_index(now: DateTime<Local>, total_groups: usize) -> usize {
 ///
 /// assert_eq!(wait_seconds, 900); // 15 minutes
 /// ```
-pub fn wait_time(interval: f64, now: DateTime<Local>) -> u64 {
+pub fn wait_time<Tz: TimeZone>(interval: f64, now: DateTime<Tz>) -> u64 {
     let current = now.minute() * 60 + now.second();
     let next = (interval * 60.0).ceil() as u32;
     let remaining = current % next;
@@ -54,6 +95,30 @@ pub fn wait_time(interval: f64, now: DateTime<Local>) -> u64 {
     wait as u64
 }
 
+/// Calculates the wait until a rotating group picks its next wallpaper.
+///
+/// Same as [`wait_time`], but never waits past the end of the group's active window,
+/// so the normal schedule takes over right when the group ends.
+///
+/// # Examples
+/// ```rust
+/// use chrono::{Local, TimeZone};
+/// use expression::utils::calc::rotation_wait;
+///
+/// let now = Local.with_ymd_and_hms(2025, 3, 31, 14, 52, 0).unwrap();
+/// let window_end = Local.with_ymd_and_hms(2025, 3, 31, 14, 55, 0).unwrap();
+///
+/// assert_eq!(rotation_wait(10.0, now, window_end), 180); // 14:55, not 15:00
+/// ```
+pub fn rotation_wait<Tz: TimeZone>(
+    interval: f64,
+    now: DateTime<Tz>,
+    window_end: DateTime<Tz>,
+) -> u64 {
+    let window_wait = (window_end - now.clone()).num_seconds().max(1) as u64;
+    wait_time(interval, now).min(window_wait)
+}
+
 /// Calculate the remaining time until the next refresh interval minute.
 ///
 /// Also detects if the hour has changed between two timestamps.
@@ -88,10 +153,10 @@ pub fn wait_time(interval: f64, now: DateTime<Local>) -> u64 {
 /// assert_eq!(is_hour_changed, false);
 /// assert_eq!(wait_seconds, 900); // 15 minutes
 /// ```
-pub fn refresh_time(
+pub fn refresh_time<Tz: TimeZone>(
     refresh_minute: f64,
-    old_now: DateTime<Local>,
-    new_now: DateTime<Local>,
+    old_now: DateTime<Tz>,
+    new_now: DateTime<Tz>,
 ) -> (bool, u64) {
     let mut is_hour_changed = false;
 
@@ -107,7 +172,45 @@ pub fn refresh_time(
     // Re-calculate refresh time
     let new_wait_seconds: u64 = wait_time(refresh_minute, new_now);
 
-    return (is_hour_changed, new_wait_seconds);
+    (is_hour_changed, new_wait_seconds)
+}
+
+/// Parses a human readable duration into seconds.
+///
+/// Accepts any combination of hours, minutes and seconds: `2h`, `30m`, `1h30m`, `90s`.
+/// Returns None for empty or malformed input.
+///
+/// # Examples
+/// ```rust
+/// use expression::utils::calc::parse_duration;
+///
+/// assert_eq!(parse_duration("1h30m"), Some(5400));
+/// assert_eq!(parse_duration("soon"), None);
+/// ```
+pub fn parse_duration(value: &str) -> Option<u64> {
+    let mut total = 0;
+    let mut number = String::new();
+    let mut has_unit = false;
+    for c in value.trim().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        total += number.parse::<u64>().ok()? * unit;
+        number.clear();
+        has_unit = true;
+    }
+    // Trailing number without a unit or empty input
+    if !number.is_empty() || !has_unit {
+        return None;
+    }
+    Some(total)
 }
 
 // █▀█ █▀▀ █▀▀ █▀█ █▀▀ █▀ █░█
@@ -117,11 +220,29 @@ use colored::Colorize;
 use log2::debug;
 use std::{thread, time::Duration};
 
+// How often a sleep checks whether it should wake up early
+const WAKE_CHECK_SECONDS: u64 = 5;
+
 /// Simply waits until next wallpaper refresh time
 pub fn sleep(wait_seconds: u64) {
     thread::sleep(Duration::from_secs(wait_seconds));
 }
 
+/// Sleeps like [`sleep`], but checks `wake` every few seconds and returns early once it's true.
+/// Returns whether it was woken early.
+pub fn sleep_or_wake(wait_seconds: u64, wake: &dyn Fn() -> bool) -> bool {
+    let mut remaining = wait_seconds;
+    while remaining > 0 {
+        let chunk = remaining.min(WAKE_CHECK_SECONDS);
+        sleep(chunk);
+        remaining -= chunk;
+        if wake() {
+            return true;
+        }
+    }
+    false
+}
+
 /// Re-calculates refresh time every T/2 seconds
 /// Mitigates the Sleep/Hibernate issue to an extent without much wakeup calls
 /// Time : Max Polling Rate [log2(refresh_seconds)]
@@ -131,7 +252,13 @@ pub fn sleep(wait_seconds: u64) {
 /// 8m  : 9     |    8h  : 15
 /// 16m : 10    |    16h : 16
 /// 32m : 11    |    32h : 17
-pub fn refresh(interval_minute: f64, start_time: DateTime<Local>, wait_seconds: u64) {
+/// Returns early once `wake` is true (checked every few seconds).
+pub fn refresh<Tz: TimeZone<Offset: Copy>>(
+    interval_minute: f64,
+    start_time: DateTime<Tz>,
+    wait_seconds: u64,
+    wake: &dyn Fn() -> bool,
+) {
     let mut previous_wait = wait_seconds;
     let mut current_wait = wait_seconds;
 
@@ -149,10 +276,13 @@ pub fn refresh(interval_minute: f64, start_time: DateTime<Local>, wait_seconds:
                 format!("{}s", format!("{current_wait}").cyan())
             }
         );
-        sleep(current_wait);
+        if sleep_or_wake(current_wait, wake) {
+            debug!("Woken up early");
+            return;
+        }
 
         // Recalculate total wait seconds
-        let now = Local::now();
+        let now = Utc::now().with_timezone(&start_time.timezone());
         let (is_hour_changed, new_wait) = refresh_time(interval_minute, start_time, now);
 
         // If the hour is changed, it probably means it's the next hour
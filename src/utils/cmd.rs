@@ -1,5 +1,7 @@
 use std::io;
-use std::process::{Command, Output};
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// Execute a shell command and return the result
 /// # Arguments
@@ -29,3 +31,49 @@ pub fn execute(cmd: &str) -> Result<Output, io::Error> {
 
     Command::new(shell).arg(flag).arg(cmd).output()
 }
+
+/// Execute a shell command, killing it if it runs longer than the timeout
+/// # Arguments
+/// * `cmd` - The command string to execute
+/// * `timeout` - How long the command may run
+/// # Returns
+/// * `Result<Option<ExitStatus>, io::Error>` - The exit status, or `None` if the command timed out
+/// # Example
+/// ```
+/// use expression::utils::cmd::execute_timeout;
+/// use std::time::Duration;
+///
+/// let status = execute_timeout("exit 0", Duration::from_secs(1)).unwrap();
+/// assert!(status.is_some_and(|status| status.success()));
+///
+/// let status = execute_timeout("sleep 5", Duration::from_millis(50)).unwrap();
+/// assert!(status.is_none());
+/// ```
+pub fn execute_timeout(cmd: &str, timeout: Duration) -> Result<Option<ExitStatus>, io::Error> {
+    let (shell, flag) = if cfg!(target_os = "windows") {
+        ("cmd", "/C")
+    } else {
+        ("bash", "-c")
+    };
+
+    let mut child = Command::new(shell)
+        .arg(flag)
+        .arg(cmd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,186 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike};
+
+// Days to look ahead for the next firing before giving up (e.g. `0 0 30 2 *` never fires)
+const MAX_SEARCH_DAYS: i64 = 366 * 5;
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Standard 5 field cron expression: `minute hour day-of-month month day-of-week`
+///
+/// Supports `*`, lists (`1,15`), ranges (`1-5`), steps (`*/15`, `8-18/2`),
+/// month and weekday names (`jan`, `mon-fri`) and macros (`@hourly`, `@daily`, `@weekly`, `@monthly`, `@yearly`).
+///
+/// Like Vixie cron, if both day-of-month and day-of-week are restricted, either one matching is enough.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u32,
+    weekdays: u32,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    /// Parses a cron expression
+    ///
+    /// # Example
+    /// ```
+    /// use expression::utils::cron::CronSchedule;
+    ///
+    /// assert!(CronSchedule::parse("0 9 * * 1-5").is_ok());
+    /// assert!(CronSchedule::parse("0 25 * * *").is_err());
+    /// ```
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(format!("expected 5 fields in `{}`", expression));
+        };
+
+        // NOTE: 7 is an alias of sunday
+        let weekdays = parse_field(weekday, 0, 7, &WEEKDAY_NAMES, 0)?;
+        let weekdays = (weekdays | (weekdays >> 7)) & 0x7f;
+
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[], 0)?,
+            hours: parse_field(hour, 0, 23, &[], 0)? as u32,
+            days: parse_field(day, 1, 31, &[], 0)? as u32,
+            months: parse_field(month, 1, 12, &MONTH_NAMES, 1)? as u32,
+            weekdays: weekdays as u32,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+
+    /// Checks if the schedule fires at the minute of the given time
+    pub fn matches<Tz: TimeZone>(&self, time: DateTime<Tz>) -> bool {
+        self.matches_date(time.date_naive())
+            && self.hours & (1 << time.hour()) != 0
+            && self.minutes & (1 << time.minute()) != 0
+    }
+
+    /// Finds the first firing strictly after the given time
+    pub fn next_after<Tz: TimeZone<Offset: Copy>>(
+        &self,
+        time: DateTime<Tz>,
+    ) -> Option<DateTime<Tz>> {
+        let start = time.date_naive();
+        for offset in 0..MAX_SEARCH_DAYS {
+            let date = start + Duration::days(offset);
+            if !self.matches_date(date) {
+                continue;
+            }
+            for hour in (0..24).filter(|h| self.hours & (1 << h) != 0) {
+                for minute in (0..60).filter(|m| self.minutes & (1 << m) != 0) {
+                    let naive = date.and_hms_opt(hour, minute, 0)?;
+                    // NOTE: Skips times which don't exist due to DST
+                    if let Some(firing) = time.timezone().from_local_datetime(&naive).earliest() {
+                        if firing > time {
+                            return Some(firing);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Finds the latest firing which is still running at the given time,
+    /// i.e. `firing <= time < firing + duration`
+    pub fn active_firing<Tz: TimeZone<Offset: Copy>>(
+        &self,
+        time: DateTime<Tz>,
+        duration: Duration,
+    ) -> Option<DateTime<Tz>> {
+        // Firing times are minute aligned, so start at the minute before the window
+        let mut candidate = self.next_after(time - duration - Duration::minutes(1))?;
+        let mut latest = None;
+        while candidate <= time {
+            if candidate > time - duration {
+                latest = Some(candidate);
+            }
+            candidate = self.next_after(candidate)?;
+        }
+        latest
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+}
+
+/// Parses a single cron field into a bitmask of allowed values
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    name_base: u32,
+) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid step in `{}`", part))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, names, name_base)?,
+                parse_value(end, names, name_base)?,
+            )
+        } else {
+            let value = parse_value(range, names, name_base)?;
+            // `5/15` means from 5 to the end
+            (value, if part.contains('/') { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("`{}` is out of range {}-{}", part, min, max));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(value: &str, names: &[&str], name_base: u32) -> Result<u32, String> {
+    if let Ok(number) = value.parse::<u32>() {
+        return Ok(number);
+    }
+    names
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(value))
+        .map(|index| index as u32 + name_base)
+        .ok_or_else(|| format!("invalid value `{}`", value))
+}
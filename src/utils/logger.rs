@@ -15,5 +15,5 @@ pub fn init() -> log2::Handle {
             .unwrap();
         log2::open(logfile.to_str().unwrap()).level("info")
     };
-    return builder.start();
+    builder.start()
 }
@@ -1,3 +1,10 @@
 pub mod calc;
 pub mod logger;
 pub mod cmd;
+pub mod appearance;
+pub mod astro;
+pub mod cron;
+pub mod season;
+pub mod solar;
+pub mod system;
+pub mod zone;
@@ -0,0 +1,97 @@
+use super::solar;
+use chrono::{DateTime, Datelike, TimeZone};
+use serde::Deserialize;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// Collection names which activate for the season
+    pub fn collection_names(&self) -> &[&str] {
+        match self {
+            Season::Spring => &["spring"],
+            Season::Summer => &["summer"],
+            Season::Autumn => &["autumn", "fall"],
+            Season::Winter => &["winter"],
+        }
+    }
+
+    /// The season at the same time of the year in the other hemisphere
+    fn opposite(&self) -> Self {
+        match self {
+            Season::Spring => Season::Autumn,
+            Season::Summer => Season::Winter,
+            Season::Autumn => Season::Spring,
+            Season::Winter => Season::Summer,
+        }
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.collection_names()[0])
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeasonBoundaries {
+    /// Whole months: March, June, September and December start a season
+    #[default]
+    Meteorological,
+    /// Equinoxes and solstices start a season
+    Astronomical,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Hemisphere {
+    #[default]
+    North,
+    South,
+}
+
+/// Determines the season at the given time.
+/// Meteorological seasons follow the local date of `time`, astronomical ones the exact instant.
+///
+/// # Example
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use expression::utils::season::{get_season, Hemisphere, Season, SeasonBoundaries};
+///
+/// let time = Utc.with_ymd_and_hms(2025, 7, 1, 12, 0, 0).unwrap();
+///
+/// let season = get_season(time, &SeasonBoundaries::Meteorological, &Hemisphere::South);
+///
+/// assert_eq!(season, Season::Winter);
+/// ```
+pub fn get_season<Tz: TimeZone>(
+    time: DateTime<Tz>,
+    boundaries: &SeasonBoundaries,
+    hemisphere: &Hemisphere,
+) -> Season {
+    let northern = match boundaries {
+        SeasonBoundaries::Meteorological => match time.month() {
+            3..=5 => Season::Spring,
+            6..=8 => Season::Summer,
+            9..=11 => Season::Autumn,
+            _ => Season::Winter,
+        },
+        SeasonBoundaries::Astronomical => match solar::ecliptic_longitude(time.to_utc()) {
+            longitude if longitude < 90.0 => Season::Spring,
+            longitude if longitude < 180.0 => Season::Summer,
+            longitude if longitude < 270.0 => Season::Autumn,
+            _ => Season::Winter,
+        },
+    };
+    match hemisphere {
+        Hemisphere::North => northern,
+        Hemisphere::South => northern.opposite(),
+    }
+}
@@ -0,0 +1,229 @@
+use super::calc::parse_duration;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use serde::Deserialize;
+use std::f64::consts::PI;
+use std::fmt;
+
+// Offline solar calculator based on the sunrise equation
+// https://en.wikipedia.org/wiki/Sunrise_equation
+
+const J2000: f64 = 2451545.0;
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+const EARTH_OBLIQUITY: f64 = 23.4397;
+
+/// Solar keyed items and entries stay active for an hour, same as `HH` keyed ones
+pub const SLOT_SECONDS: i64 = 3600;
+
+/// Observer position in degrees (north and east are positive)
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SolarEvent {
+    CivilDawn,
+    Sunrise,
+    SolarNoon,
+    Sunset,
+    CivilDusk,
+}
+
+impl SolarEvent {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "civil_dawn" => Some(Self::CivilDawn),
+            "sunrise" => Some(Self::Sunrise),
+            "solar_noon" => Some(Self::SolarNoon),
+            "sunset" => Some(Self::Sunset),
+            "civil_dusk" => Some(Self::CivilDusk),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Self::CivilDawn => "civil_dawn",
+            Self::Sunrise => "sunrise",
+            Self::SolarNoon => "solar_noon",
+            Self::Sunset => "sunset",
+            Self::CivilDusk => "civil_dusk",
+        }
+    }
+}
+
+/// Solar event with an optional offset, e.g. `sunset-30m` or `sunrise+1h`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarAnchor {
+    pub event: SolarEvent,
+    pub offset_seconds: i64,
+}
+
+impl SolarAnchor {
+    /// Parses anchors like `sunrise`, `sunset-30m`, `civil_dusk+1h30m`.
+    /// Returns None if the name is not a solar anchor.
+    pub fn parse(value: &str) -> Option<Self> {
+        let split = value.find(['+', '-']);
+        let (name, offset) = match split {
+            Some(index) => value.split_at(index),
+            None => (value, ""),
+        };
+        let event = SolarEvent::from_name(name)?;
+        let offset_seconds = match offset.split_at_checked(1) {
+            Some(("+", duration)) => parse_duration(duration)? as i64,
+            Some(("-", duration)) => -(parse_duration(duration)? as i64),
+            _ => 0,
+        };
+        Some(Self {
+            event,
+            offset_seconds,
+        })
+    }
+
+    /// Time of the anchor on the given date, in the given time zone.
+    /// Returns None if the sun never reaches the event altitude (polar day/night).
+    pub fn time_on<Tz: TimeZone>(
+        &self,
+        date: NaiveDate,
+        location: &Location,
+        zone: &Tz,
+    ) -> Option<DateTime<Tz>> {
+        let time = event_time(self.event, date, location)?;
+        Some(time.with_timezone(zone) + Duration::seconds(self.offset_seconds))
+    }
+}
+
+impl fmt::Display for SolarAnchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.event.name())?;
+        let sign = if self.offset_seconds < 0 { '-' } else { '+' };
+        let offset = self.offset_seconds.unsigned_abs();
+        match offset {
+            0 => Ok(()),
+            _ if offset.is_multiple_of(3600) => write!(f, "{}{}h", sign, offset / 3600),
+            _ if offset.is_multiple_of(60) => write!(f, "{}{}m", sign, offset / 60),
+            _ => write!(f, "{}{}s", sign, offset),
+        }
+    }
+}
+
+/// Calculates the UTC time of a solar event on a given date.
+///
+/// Returns None if the event does not happen on that date
+/// (e.g. no sunset during polar day).
+///
+/// # Example
+/// ```
+/// use chrono::NaiveDate;
+/// use expression::utils::solar::{event_time, Location, SolarEvent};
+///
+/// let london = Location { latitude: 51.5, longitude: -0.13 };
+/// let date = NaiveDate::from_ymd_opt(2025, 6, 21).unwrap();
+///
+/// let sunrise = event_time(SolarEvent::Sunrise, date, &london);
+/// assert!(sunrise.is_some());
+/// ```
+pub fn event_time(
+    event: SolarEvent,
+    date: NaiveDate,
+    location: &Location,
+) -> Option<DateTime<Utc>> {
+    // Julian date at noon UTC of the given date
+    let noon = date.and_hms_opt(12, 0, 0)?.and_utc();
+    let julian_day = noon.timestamp() as f64 / 86400.0 + UNIX_EPOCH_JD;
+
+    // Mean solar time
+    let n = (julian_day - J2000 + 0.0008).round();
+    let mean_solar_time = n - location.longitude / 360.0;
+
+    let anomaly = (357.5291 + 0.98560028 * mean_solar_time).rem_euclid(360.0);
+    let ecliptic = ecliptic_longitude_from_anomaly(anomaly);
+    let transit =
+        J2000 + mean_solar_time + 0.0053 * sin_deg(anomaly) - 0.0069 * sin_deg(2.0 * ecliptic);
+
+    let altitude = match event {
+        SolarEvent::SolarNoon => return julian_to_utc(transit),
+        SolarEvent::Sunrise | SolarEvent::Sunset => -0.833,
+        SolarEvent::CivilDawn | SolarEvent::CivilDusk => -6.0,
+    };
+
+    let declination = (sin_deg(ecliptic) * sin_deg(EARTH_OBLIQUITY)).asin();
+    let latitude = location.latitude.to_radians();
+    let cos_hour_angle = (sin_deg(altitude) - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+
+    match event {
+        SolarEvent::CivilDawn | SolarEvent::Sunrise => julian_to_utc(transit - hour_angle / 360.0),
+        _ => julian_to_utc(transit + hour_angle / 360.0),
+    }
+}
+
+/// Finds the earliest anchor time strictly after `now`, including slot ends.
+/// Slots last `slot_seconds` from their anchor time.
+pub fn next_boundary<Tz: TimeZone<Offset: Copy>>(
+    anchors: &[SolarAnchor],
+    now: DateTime<Tz>,
+    location: &Location,
+    slot_seconds: i64,
+) -> Option<DateTime<Tz>> {
+    let zone = now.timezone();
+    let today = now.date_naive();
+    let dates = [today.pred_opt()?, today, today.succ_opt()?];
+    anchors
+        .iter()
+        .flat_map(|anchor| {
+            dates
+                .iter()
+                .filter_map(|date| anchor.time_on(*date, location, &zone))
+        })
+        .flat_map(|start| [start, start + Duration::seconds(slot_seconds)])
+        .filter(|time| *time > now)
+        .min()
+}
+
+/// Start of the currently active slot of the anchor, if any
+pub fn slot_start<Tz: TimeZone<Offset: Copy>>(
+    anchor: &SolarAnchor,
+    now: DateTime<Tz>,
+    location: &Location,
+    slot_seconds: i64,
+) -> Option<DateTime<Tz>> {
+    let zone = now.timezone();
+    let today = now.date_naive();
+    [today.pred_opt(), Some(today)]
+        .into_iter()
+        .flatten()
+        .filter_map(|date| anchor.time_on(date, location, &zone))
+        .filter(|start| *start <= now && now < *start + Duration::seconds(slot_seconds))
+        .max()
+}
+
+/// Apparent ecliptic longitude of the sun in degrees (0 at the March equinox, 90 at the June solstice)
+/// Low precision formula from the Astronomical Almanac, good to about 0.01 degrees
+pub fn ecliptic_longitude(time: DateTime<Utc>) -> f64 {
+    let days = time.timestamp() as f64 / 86400.0 + UNIX_EPOCH_JD - J2000;
+    let mean_longitude = 280.460 + 0.9856474 * days;
+    let anomaly = 357.528 + 0.9856003 * days;
+    (mean_longitude + 1.915 * sin_deg(anomaly) + 0.020 * sin_deg(2.0 * anomaly)).rem_euclid(360.0)
+}
+
+fn ecliptic_longitude_from_anomaly(anomaly: f64) -> f64 {
+    let center = 1.9148 * sin_deg(anomaly)
+        + 0.0200 * sin_deg(2.0 * anomaly)
+        + 0.0003 * sin_deg(3.0 * anomaly);
+    (anomaly + center + 180.0 + 102.9372).rem_euclid(360.0)
+}
+
+fn julian_to_utc(julian_day: f64) -> Option<DateTime<Utc>> {
+    let seconds = ((julian_day - UNIX_EPOCH_JD) * 86400.0).round() as i64;
+    Utc.timestamp_opt(seconds, 0).single()
+}
+
+fn sin_deg(degrees: f64) -> f64 {
+    (degrees * PI / 180.0).sin()
+}
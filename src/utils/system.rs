@@ -0,0 +1,36 @@
+use std::{fs, path::Path};
+
+/// Battery charge in percent and whether it is charging, from the first battery in
+/// `/sys/class/power_supply`. None on machines without a battery (or without sysfs).
+pub fn battery() -> Option<(f64, bool)> {
+    let supplies = fs::read_dir("/sys/class/power_supply").ok()?;
+    supplies.flatten().find_map(|supply| {
+        let path = supply.path();
+        if read_trimmed(&path.join("type"))? != "Battery" {
+            return None;
+        }
+        let capacity = read_trimmed(&path.join("capacity"))?.parse().ok()?;
+        let status = read_trimmed(&path.join("status")).unwrap_or_default();
+        // "Not charging" means plugged in but held at a charge limit
+        let charging = matches!(status.as_str(), "Charging" | "Full" | "Not charging");
+        Some((capacity, charging))
+    })
+}
+
+/// Number of connected displays, from `/sys/class/drm`. None without sysfs.
+pub fn output_count() -> Option<usize> {
+    let connectors = fs::read_dir("/sys/class/drm").ok()?;
+    let count = connectors
+        .flatten()
+        .filter(|connector| {
+            read_trimmed(&connector.path().join("status")).as_deref() == Some("connected")
+        })
+        .count();
+    Some(count)
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_string())
+}
@@ -0,0 +1,114 @@
+use chrono::{
+    DateTime, FixedOffset, Local, MappedLocalTime, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use std::fmt;
+
+/// Time zone the schedule runs in.
+/// Either the system zone, or an IANA zone from `timezone` in `[general]`.
+///
+/// Time zone data is bundled with the binary, so named zones work offline.
+///
+/// # Example
+/// ```
+/// use chrono::{TimeZone, Timelike, Utc};
+/// use expression::utils::zone::Zone;
+///
+/// let tokyo = Zone::parse("Asia/Tokyo").unwrap();
+/// let noon_utc = Utc.with_ymd_and_hms(2025, 3, 31, 12, 0, 0).unwrap();
+///
+/// assert_eq!(noon_utc.with_timezone(&tokyo).hour(), 21);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Zone {
+    #[default]
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    /// Parses an IANA time zone name, e.g. `Asia/Tokyo`
+    pub fn parse(name: &str) -> Result<Self, String> {
+        name.parse::<Tz>()
+            .map(Self::Named)
+            .map_err(|_| format!("unknown time zone `{}`", name))
+    }
+
+    /// Current time in the zone
+    pub fn now(&self) -> DateTime<Self> {
+        Utc::now().with_timezone(self)
+    }
+
+    fn offset(&self, offset: impl Offset) -> ZoneOffset {
+        ZoneOffset {
+            zone: *self,
+            offset: offset.fix(),
+        }
+    }
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+/// UTC offset of a [`Zone`] at a given time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZoneOffset {
+    zone: Zone,
+    offset: FixedOffset,
+}
+
+impl Offset for ZoneOffset {
+    fn fix(&self) -> FixedOffset {
+        self.offset
+    }
+}
+
+impl fmt::Display for ZoneOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.offset)
+    }
+}
+
+impl TimeZone for Zone {
+    type Offset = ZoneOffset;
+
+    fn from_offset(offset: &ZoneOffset) -> Self {
+        offset.zone
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<ZoneOffset> {
+        match self {
+            Self::Local => Local.offset_from_local_date(local).map(|o| self.offset(o)),
+            Self::Named(tz) => tz.offset_from_local_date(local).map(|o| self.offset(o)),
+        }
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<ZoneOffset> {
+        match self {
+            Self::Local => Local
+                .offset_from_local_datetime(local)
+                .map(|o| self.offset(o)),
+            Self::Named(tz) => tz.offset_from_local_datetime(local).map(|o| self.offset(o)),
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> ZoneOffset {
+        match self {
+            Self::Local => self.offset(Local.offset_from_utc_date(utc)),
+            Self::Named(tz) => self.offset(tz.offset_from_utc_date(utc)),
+        }
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> ZoneOffset {
+        match self {
+            Self::Local => self.offset(Local.offset_from_utc_datetime(utc)),
+            Self::Named(tz) => self.offset(tz.offset_from_utc_datetime(utc)),
+        }
+    }
+}
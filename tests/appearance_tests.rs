@@ -0,0 +1,59 @@
+use expression::config::{AppearanceConfig, SchemeSource};
+use expression::utils::appearance::{self, ColorScheme};
+use std::fs;
+mod utils;
+use utils::{cleanup_test_dir, setup_test_dir};
+
+#[test]
+fn test_from_portal_reply() {
+    assert_eq!(
+        ColorScheme::from_portal("(<<uint32 1>>,)\n"),
+        Some(ColorScheme::Dark)
+    );
+    assert_eq!(
+        ColorScheme::from_portal("(<<uint32 2>>,)"),
+        Some(ColorScheme::Light)
+    );
+    // No preference
+    assert_eq!(
+        ColorScheme::from_portal("(<uint32 0>,)"),
+        Some(ColorScheme::Light)
+    );
+    assert_eq!(ColorScheme::from_portal("(<<uint32 7>>,)"), None);
+    assert_eq!(ColorScheme::from_portal(""), None);
+}
+
+#[test]
+fn test_read_scheme_file() {
+    let test_dir = std::env::temp_dir().join("test_read_scheme_file");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &[], &[]).unwrap();
+    let file = test_dir.join("scheme");
+
+    // Missing file can't be read
+    assert_eq!(appearance::detect(SchemeSource::File, Some(&file)), None);
+
+    fs::write(&file, "dark\n").unwrap();
+    assert_eq!(
+        appearance::detect(SchemeSource::Auto, Some(&file)),
+        Some((SchemeSource::File, ColorScheme::Dark))
+    );
+
+    fs::write(&file, "prefer-light").unwrap();
+    assert_eq!(
+        appearance::read(SchemeSource::File, Some(&file)),
+        Some(ColorScheme::Light)
+    );
+
+    cleanup_test_dir(&test_dir);
+}
+
+#[test]
+fn test_scheme_collection_names() {
+    let config = AppearanceConfig {
+        dark: Some("Night".into()),
+        ..Default::default()
+    };
+    assert_eq!(config.collection(ColorScheme::Dark), "Night");
+    assert_eq!(config.collection(ColorScheme::Light), "light");
+}
@@ -0,0 +1,85 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use expression::utils::astro::{get_moon_age, get_moon_phase, AstroCondition, MoonPhase};
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn condition(value: &str) -> AstroCondition {
+    AstroCondition::parse(value).unwrap().unwrap()
+}
+
+// █▀▄▀█ █▀█ █▀█ █▄░█
+// █░▀░█ █▄█ █▄█ █░▀█
+
+#[test]
+fn test_get_moon_phase() {
+    // Full moon 2025-01-13 22:27 UTC, new moon 2025-01-29 12:36 UTC
+    let cases = [
+        (6, MoonPhase::Waxing),
+        (13, MoonPhase::Full),
+        (21, MoonPhase::Waning),
+        (29, MoonPhase::New),
+    ];
+    for (day, expected) in cases {
+        let time = Utc.with_ymd_and_hms(2025, 1, day, 12, 0, 0).unwrap();
+        assert_eq!(
+            get_moon_phase(time),
+            expected,
+            "Unexpected phase on {}",
+            day
+        );
+    }
+}
+
+#[test]
+fn test_get_moon_age_near_new_moon() {
+    let new_moon = Utc.with_ymd_and_hms(2025, 9, 21, 19, 54, 0).unwrap();
+    let age = get_moon_age(new_moon);
+    // Mean phase is off by up to a day
+    assert!(!(1.0..=28.5).contains(&age), "Unexpected age {}", age);
+}
+
+// █▀▀ █▀█ █▄░█ █▀▄ █ ▀█▀ █ █▀█ █▄░█ █▀
+// █▄▄ █▄█ █░▀█ █▄▀ █ ░█░ █ █▄█ █░▀█ ▄█
+
+#[test]
+fn test_parse_conditions() {
+    assert_eq!(
+        condition("moon:full"),
+        AstroCondition::Moon(MoonPhase::Full)
+    );
+    assert_eq!(condition("solstice"), AstroCondition::Solstice);
+    assert_eq!(
+        condition("meteor:perseids"),
+        AstroCondition::MeteorShower(Some("perseids".into()))
+    );
+    assert_eq!(condition("meteor_shower").to_string(), "meteor_shower");
+
+    assert!(AstroCondition::parse("moon:blue").unwrap().is_err());
+    assert!(AstroCondition::parse("meteor:unknown").unwrap().is_err());
+    assert!(AstroCondition::parse("12-25").is_none());
+}
+
+#[test]
+fn test_solstice_and_equinox_days() {
+    let solstice = condition("solstice");
+    assert!(solstice.is_met(date(2025, 6, 21)));
+    assert!(solstice.is_met(date(2025, 12, 21)));
+    assert!(!solstice.is_met(date(2025, 6, 20)));
+    assert!(!solstice.is_met(date(2025, 6, 22)));
+
+    let equinox = condition("equinox");
+    assert!(equinox.is_met(date(2025, 3, 20)));
+    assert!(equinox.is_met(date(2025, 9, 22)));
+    assert!(!equinox.is_met(date(2025, 3, 21)));
+    assert!(!equinox.is_met(date(2025, 9, 23)));
+}
+
+#[test]
+fn test_meteor_shower_peaks() {
+    assert!(condition("meteor_shower").is_met(date(2025, 8, 12)));
+    assert!(condition("meteor:geminids").is_met(date(2025, 12, 14)));
+    assert!(!condition("meteor:geminids").is_met(date(2025, 8, 12)));
+    assert!(!condition("meteor_shower").is_met(date(2025, 8, 13)));
+}
@@ -1,5 +1,7 @@
 use chrono::{Local, TimeZone};
-use expression::utils::calc::{refresh_time, wait_time};
+use expression::utils::calc::{
+    get_span_index, parse_duration, refresh_time, rotation_wait, sleep_or_wake, wait_time,
+};
 
 // █░█░█ ▄▀█ █ ▀█▀   ▀█▀ █ █▀▄▀█ █▀▀
 // ▀▄▀▄▀ █▀█ █ ░█░   ░█░ █ █░▀░█ ██▄
@@ -121,7 +123,25 @@ fn test_wait_time_high_precision_decimal() {
     let interval = 3.333; // ~3 min 20 sec
     let now = Local.with_ymd_and_hms(2025, 3, 31, 14, 3, 20).unwrap();
     let wait_seconds = wait_time(interval, now);
-    assert!(wait_seconds <= 200 && wait_seconds >= 180); // loose check: ~200s
+    assert!((180..=200).contains(&wait_seconds)); // loose check: ~200s
+}
+
+#[test]
+fn test_rotation_wait_within_window() {
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 14, 22, 0).unwrap();
+    let window_end = Local.with_ymd_and_hms(2025, 3, 31, 15, 0, 0).unwrap();
+
+    assert_eq!(rotation_wait(10.0, now, window_end), 480); // 14:30
+}
+
+#[test]
+fn test_rotation_wait_clipped_to_window_end() {
+    // A 25 minute rotation would run past the hour (15:15), hand back at 15:00 instead
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 14, 52, 0).unwrap();
+    let window_end = Local.with_ymd_and_hms(2025, 3, 31, 15, 0, 0).unwrap();
+
+    assert_eq!(wait_time(25.0, now), 1380);
+    assert_eq!(rotation_wait(25.0, now, window_end), 480);
 }
 
 // █▀█ █▀▀ █▀▀ █▀█ █▀▀ █▀ █░█   ▀█▀ █ █▀▄▀█ █▀▀
@@ -135,7 +155,7 @@ fn test_refresh_time_same_hour() {
 
     let (is_hour_changed, wait_seconds) = refresh_time(interval, old_now, new_now);
 
-    assert_eq!(is_hour_changed, false);
+    assert!(!is_hour_changed);
     assert_eq!(wait_seconds, 900); // 15 minutes
 }
 
@@ -147,7 +167,7 @@ fn test_refresh_time_hour_changed() {
 
     let (is_hour_changed, wait_seconds) = refresh_time(interval, old_now, new_now);
 
-    assert_eq!(is_hour_changed, true);
+    assert!(is_hour_changed);
     assert_eq!(wait_seconds, 0);
 }
 
@@ -159,7 +179,7 @@ fn test_refresh_time_midnight_change() {
 
     let (is_hour_changed, wait_seconds) = refresh_time(interval, old_now, new_now);
 
-    assert_eq!(is_hour_changed, true);
+    assert!(is_hour_changed);
     assert_eq!(wait_seconds, 0);
 }
 
@@ -171,7 +191,7 @@ fn test_refresh_time_time_travel() {
 
     let (is_hour_changed, wait_seconds) = refresh_time(interval, old_now, new_now);
 
-    assert_eq!(is_hour_changed, false);
+    assert!(!is_hour_changed);
     assert_eq!(wait_seconds, 300); // 5 minutes remaining
 }
 
@@ -183,6 +203,72 @@ fn test_refresh_time_exact_interval() {
 
     let (is_hour_changed, wait_seconds) = refresh_time(interval, old_now, new_now);
 
-    assert_eq!(is_hour_changed, false);
+    assert!(!is_hour_changed);
     assert_eq!(wait_seconds, 1800); // 30 minutes
 }
+
+#[test]
+fn test_sleep_or_wake_returns_early() {
+    let start = std::time::Instant::now();
+    assert!(sleep_or_wake(60, &|| true));
+    assert!(start.elapsed().as_secs() < 60);
+
+    assert!(!sleep_or_wake(0, &|| true));
+}
+
+// █▀▄ █░█ █▀█ ▄▀█ ▀█▀ █ █▀█ █▄░█
+// █▄▀ █▄█ █▀▄ █▀█ ░█░ █ █▄█ █░▀█
+
+#[test]
+fn test_parse_duration_units() {
+    assert_eq!(parse_duration("2h"), Some(7200));
+    assert_eq!(parse_duration("30m"), Some(1800));
+    assert_eq!(parse_duration("45s"), Some(45));
+    assert_eq!(parse_duration("1h30m"), Some(5400));
+    assert_eq!(parse_duration("0m"), Some(0));
+}
+
+#[test]
+fn test_parse_duration_invalid() {
+    assert_eq!(parse_duration(""), None);
+    assert_eq!(parse_duration("30"), None);
+    assert_eq!(parse_duration("1h30"), None);
+    assert_eq!(parse_duration("m"), None);
+    assert_eq!(parse_duration("1d"), None);
+}
+
+// █▀ █▀█ █▀█ █▀▀ ▄▀█ █▀▄
+// ▄█ █▀▀ █▀▄ ██▄ █▀█ █▄▀
+
+#[test]
+fn test_get_span_index_overnight_window() {
+    let start = Local.with_ymd_and_hms(2025, 3, 31, 22, 0, 0).unwrap();
+    let end = Local.with_ymd_and_hms(2025, 4, 1, 6, 0, 0).unwrap();
+
+    // 8 hours, 4 wallpapers: 2 hours each
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 22, 0, 0).unwrap();
+    assert_eq!(get_span_index(start, end, now, 4), (0, 7200));
+
+    let now = Local.with_ymd_and_hms(2025, 4, 1, 5, 59, 59).unwrap();
+    assert_eq!(get_span_index(start, end, now, 4), (3, 1));
+}
+
+#[test]
+fn test_get_span_index_second_precision() {
+    let start = Local.with_ymd_and_hms(2025, 3, 31, 14, 0, 0).unwrap();
+    let end = Local.with_ymd_and_hms(2025, 3, 31, 15, 0, 0).unwrap();
+
+    // More wallpapers than minutes in the window: 30 seconds each
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 14, 30, 15).unwrap();
+    assert_eq!(get_span_index(start, end, now, 120), (60, 15));
+}
+
+#[test]
+fn test_get_span_index_uneven_slices() {
+    let start = Local.with_ymd_and_hms(2025, 3, 31, 14, 0, 0).unwrap();
+    let end = Local.with_ymd_and_hms(2025, 3, 31, 15, 0, 0).unwrap();
+
+    // 3600 / 7 = 514.28s per wallpaper, next change rounds up to 1029s
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 14, 10, 0).unwrap();
+    assert_eq!(get_span_index(start, end, now, 7), (1, 429));
+}
@@ -0,0 +1,180 @@
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Europe::Berlin;
+use expression::config::CalendarConfig;
+use expression::core::calendar::{active_events, next_change, parse_calendar, CalendarFile, Event};
+use std::{
+    fs, io,
+    time::{Duration, SystemTime},
+};
+
+mod utils;
+use utils::{cleanup_test_dir, setup_test_dir};
+
+fn calendar(events: &[&str]) -> String {
+    let events: Vec<String> = events
+        .iter()
+        .map(|event| {
+            format!(
+                "BEGIN:VEVENT\r\n{}\r\nEND:VEVENT",
+                event.replace('\n', "\r\n")
+            )
+        })
+        .collect();
+    format!(
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}\r\nEND:VCALENDAR\r\n",
+        events.join("\r\n")
+    )
+}
+
+fn active_summaries(events: &[Event], now: DateTime<Utc>) -> Vec<String> {
+    active_events(events, now)
+        .into_iter()
+        .map(|(event, _, _)| event.summary.clone())
+        .collect()
+}
+
+#[test]
+fn test_single_event_respects_start_and_end() {
+    let events = parse_calendar(&calendar(&[
+        "SUMMARY:Launch\nDTSTART:20261020T090000Z\nDTEND:20261020T100000Z",
+    ]));
+
+    let before = Utc.with_ymd_and_hms(2026, 10, 20, 8, 59, 59).unwrap();
+    let during = Utc.with_ymd_and_hms(2026, 10, 20, 9, 0, 0).unwrap();
+    let after = Utc.with_ymd_and_hms(2026, 10, 20, 10, 0, 0).unwrap();
+
+    assert!(active_summaries(&events, before).is_empty());
+    assert_eq!(active_summaries(&events, during), vec!["Launch"]);
+    assert!(active_summaries(&events, after).is_empty());
+    assert_eq!(next_change(&events, before), Some(during));
+    assert_eq!(next_change(&events, during), Some(after));
+}
+
+#[test]
+fn test_all_day_event_and_time_zones() {
+    let events = parse_calendar(&calendar(&[
+        "SUMMARY:Holiday\nDTSTART;VALUE=DATE:20261225\nDTEND;VALUE=DATE:20261227",
+        "SUMMARY:Standup\nDTSTART;TZID=Europe/Berlin:20261020T090000\nDURATION:PT15M",
+    ]));
+
+    // All day events follow the schedule's zone
+    let christmas_eve = Berlin.with_ymd_and_hms(2026, 12, 24, 23, 0, 0).unwrap();
+    let boxing_day = Berlin.with_ymd_and_hms(2026, 12, 26, 23, 0, 0).unwrap();
+    assert!(active_events(&events, christmas_eve).is_empty());
+    assert_eq!(active_events(&events, boxing_day)[0].0.summary, "Holiday");
+
+    // 09:00 in Berlin is 07:00 UTC in summer time
+    let standup = Utc.with_ymd_and_hms(2026, 10, 20, 7, 10, 0).unwrap();
+    assert_eq!(active_summaries(&events, standup), vec!["Standup"]);
+}
+
+#[test]
+fn test_weekly_recurrence_with_count_and_exdate() {
+    // Mondays and Wednesdays from Mon 2026-10-05, six times, without Wed 2026-10-14
+    let events = parse_calendar(&calendar(&[
+        "SUMMARY:Focus\nDTSTART:20261005T140000\nDTEND:20261005T160000\n\
+         RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=6\nEXDATE:20261014T140000",
+    ]));
+    let at = |day| Utc.with_ymd_and_hms(2026, 10, day, 15, 0, 0).unwrap();
+
+    assert_eq!(active_summaries(&events, at(5)), vec!["Focus"]);
+    assert_eq!(active_summaries(&events, at(7)), vec!["Focus"]);
+    assert!(active_summaries(&events, at(6)).is_empty());
+    assert!(active_summaries(&events, at(14)).is_empty());
+    // Left out occurrences still count, so Wed 2026-10-21 is the last one
+    assert_eq!(active_summaries(&events, at(21)), vec!["Focus"]);
+    assert!(active_summaries(&events, at(26)).is_empty());
+}
+
+#[test]
+fn test_monthly_and_yearly_recurrence() {
+    let events = parse_calendar(&calendar(&[
+        "SUMMARY:Review\nDTSTART;VALUE=DATE:20260130\nRRULE:FREQ=MONTHLY;BYDAY=-1FR",
+        "SUMMARY:Thanksgiving\nDTSTART;VALUE=DATE:20201126\n\
+         RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=4TH;UNTIL=20301231",
+    ]));
+    let on = |month, day| Utc.with_ymd_and_hms(2026, month, day, 12, 0, 0).unwrap();
+
+    // Last Friday of the month
+    assert_eq!(active_summaries(&events, on(10, 30)), vec!["Review"]);
+    assert!(active_summaries(&events, on(10, 23)).is_empty());
+    // Fourth Thursday of November
+    assert_eq!(active_summaries(&events, on(11, 26)), vec!["Thanksgiving"]);
+    assert!(active_summaries(&events, on(11, 19)).is_empty());
+}
+
+#[test]
+fn test_changed_and_cancelled_instances() {
+    let events = parse_calendar(&calendar(&[
+        "UID:sync\nSUMMARY:Sync\nDTSTART:20261019T100000Z\nDTEND:20261019T110000Z\n\
+         RRULE:FREQ=DAILY",
+        // Moved from 10:00 to 15:00 on the 20th
+        "UID:sync\nRECURRENCE-ID:20261020T100000Z\nSUMMARY:Sync\n\
+         DTSTART:20261020T150000Z\nDTEND:20261020T160000Z",
+        "SUMMARY:Party\nSTATUS:CANCELLED\nDTSTART:20261020T100000Z\nDTEND:20261020T110000Z",
+    ]));
+
+    let moved_from = Utc.with_ymd_and_hms(2026, 10, 20, 10, 30, 0).unwrap();
+    let moved_to = Utc.with_ymd_and_hms(2026, 10, 20, 15, 30, 0).unwrap();
+    let next_day = Utc.with_ymd_and_hms(2026, 10, 21, 10, 30, 0).unwrap();
+
+    assert!(active_summaries(&events, moved_from).is_empty());
+    assert_eq!(active_summaries(&events, moved_to), vec!["Sync"]);
+    assert_eq!(active_summaries(&events, next_day), vec!["Sync"]);
+}
+
+#[test]
+fn test_event_items_from_summary_and_categories() {
+    let events = parse_calendar(&calendar(&[
+        "SUMMARY:Q4 Release\\, final\nCATEGORIES:Launch,Work\n\
+         DTSTART:20261020T090000Z\nDTEND:20261020T100000Z",
+        "SUMMARY:lunch\nDTSTART:20261020T120000Z\nDTEND:20261020T130000Z\nBEGIN:VALARM\n\
+         SUMMARY:Reminder\nEND:VALARM",
+    ]));
+    let mut config = CalendarConfig::default();
+    config.events.insert("launch".into(), "launch_day".into());
+
+    assert_eq!(events[0].summary, "Q4 Release, final");
+    assert_eq!(
+        config.items(&events[0]),
+        vec!["launch_day", "Q4 Release, final", "Launch", "Work"]
+    );
+    // Alarms don't override the event's summary
+    assert_eq!(config.items(&events[1]), vec!["lunch"]);
+}
+
+#[test]
+fn test_calendar_file_reloads_on_change() -> io::Result<()> {
+    let test_dir = std::env::temp_dir().join("test_calendar_file_reloads_on_change");
+    setup_test_dir(&test_dir, &[], &[])?;
+    let path = test_dir.join("team.ics");
+    fs::write(&path, calendar(&["SUMMARY:One\nDTSTART:20261020T090000Z"]))?;
+
+    let mut calendar_file = CalendarFile::new(&path);
+    assert!(calendar_file.refresh()?);
+    assert!(!calendar_file.refresh()?);
+    assert!(!calendar_file.is_changed());
+    assert_eq!(calendar_file.events.len(), 1);
+
+    // Folded lines continue with a space
+    fs::write(
+        &path,
+        calendar(&[
+            "SUMMARY:Tw\n o\nDTSTART:20261020T090000Z",
+            "SUMMARY:Three\nDTSTART:20261021T090000Z",
+        ]),
+    )?;
+    let later = SystemTime::now() + Duration::from_secs(10);
+    fs::File::options()
+        .write(true)
+        .open(&path)?
+        .set_modified(later)?;
+
+    assert!(calendar_file.is_changed());
+    assert!(calendar_file.refresh()?);
+    assert_eq!(calendar_file.events.len(), 2);
+    assert_eq!(calendar_file.events[0].summary, "Two");
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use chrono::{NaiveDate, NaiveTime};
+use expression::cli::Command;
+use expression::config::RandomScope;
+use expression::core::state::{RandomOverride, Session, SessionPhase, State};
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn test_parse_collection_commands() {
+    assert_eq!(
+        Command::parse(&args("collection")),
+        Ok(Command::ShowCollection)
+    );
+    assert_eq!(
+        Command::parse(&args("collection Nature")),
+        Ok(Command::SetCollection("Nature".into()))
+    );
+    assert_eq!(
+        Command::parse(&args("collection --clear")),
+        Ok(Command::ClearCollection)
+    );
+}
+
+#[test]
+fn test_parse_collection_name_with_spaces() {
+    assert_eq!(
+        Command::parse(&args("collection Nature Collection by Twice")),
+        Ok(Command::SetCollection("Nature Collection by Twice".into()))
+    );
+}
+
+#[test]
+fn test_parse_unknown_command() {
+    assert_eq!(Command::parse(&args("--help")), Ok(Command::Help));
+    assert!(Command::parse(&args("colection Nature")).is_err());
+}
+
+#[test]
+fn test_parse_random_commands() {
+    assert_eq!(Command::parse(&args("random")), Ok(Command::ShowRandom));
+    assert_eq!(
+        Command::parse(&args("random all")),
+        Ok(Command::SetRandom(RandomScope::All, None))
+    );
+    assert_eq!(
+        Command::parse(&args("random collection 2h")),
+        Ok(Command::SetRandom(RandomScope::Collection, Some(7200)))
+    );
+    assert_eq!(
+        Command::parse(&args("random --clear")),
+        Ok(Command::ClearRandom)
+    );
+
+    assert!(Command::parse(&args("random everything")).is_err());
+    assert!(Command::parse(&args("random hour soon")).is_err());
+}
+
+#[test]
+fn test_random_override_expires() {
+    let random = RandomOverride {
+        scope: RandomScope::Hour,
+        until: Some(1000),
+    };
+    assert!(random.is_active(999));
+    assert!(!random.is_active(1000));
+
+    let forever = RandomOverride {
+        scope: RandomScope::Hour,
+        until: None,
+    };
+    assert!(forever.is_active(i64::MAX));
+}
+
+#[test]
+fn test_parse_session_commands() {
+    assert_eq!(Command::parse(&args("session")), Ok(Command::ShowSession));
+    assert_eq!(
+        Command::parse(&args("session start")),
+        Ok(Command::StartSession {
+            rounds: None,
+            focus: None,
+            rest: None
+        })
+    );
+    assert_eq!(
+        Command::parse(&args("session start 2 50m 0m")),
+        Ok(Command::StartSession {
+            rounds: Some(2),
+            focus: Some(3000),
+            rest: Some(0)
+        })
+    );
+    assert_eq!(
+        Command::parse(&args("session --clear")),
+        Ok(Command::ClearSession)
+    );
+
+    assert!(Command::parse(&args("session start 0")).is_err());
+    assert!(Command::parse(&args("session start 4 0m")).is_err());
+    assert!(Command::parse(&args("session start 4 25m 5m 1")).is_err());
+}
+
+#[test]
+fn test_session_phases_then_normal_schedule() {
+    let session = Session {
+        start: 1000,
+        rounds: 3,
+        focus: 1500,
+        rest: 300,
+        focus_item: "focus".into(),
+        break_item: "break".into(),
+    };
+
+    assert_eq!(session.phase(999), None);
+    assert_eq!(
+        session.phase(1000),
+        Some((SessionPhase::Focus, 1, 1000, 2500))
+    );
+    assert_eq!(
+        session.phase(2500),
+        Some((SessionPhase::Break, 1, 2500, 2800))
+    );
+    assert_eq!(
+        session.phase(6399),
+        Some((SessionPhase::Break, 3, 6100, 6400))
+    );
+    assert_eq!(session.end(), 6400);
+    assert_eq!(session.phase(6400), None);
+    assert_eq!(session.item(SessionPhase::Break), "break");
+}
+
+#[test]
+fn test_parse_override_commands() {
+    let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+    assert_eq!(
+        Command::parse(&args("override add launch_day 2026-10-20 09:00 1h30m")),
+        Ok(Command::AddOverride {
+            item: "launch_day".into(),
+            date: NaiveDate::from_ymd_opt(2026, 10, 20),
+            time: nine,
+            duration: 5400,
+        })
+    );
+    // Without a date and duration: the next 09:00, for an hour
+    assert_eq!(
+        Command::parse(&args("override add launch_day 09:00")),
+        Ok(Command::AddOverride {
+            item: "launch_day".into(),
+            date: None,
+            time: nine,
+            duration: 3600,
+        })
+    );
+    assert_eq!(
+        Command::parse(&args("override list")),
+        Ok(Command::ListOverrides)
+    );
+    assert_eq!(
+        Command::parse(&args("override cancel #3")),
+        Ok(Command::CancelOverride(3))
+    );
+
+    assert!(Command::parse(&args("override add launch_day 2026-10-20")).is_err());
+    assert!(Command::parse(&args("override add launch_day 25:00")).is_err());
+    assert!(Command::parse(&args("override add launch_day 09:00 0m")).is_err());
+    assert!(Command::parse(&args("override cancel first")).is_err());
+}
+
+#[test]
+fn test_overrides_activate_and_expire() {
+    let mut state = State::default();
+    let first = state.add_override("announcement".into(), 1000, 2000);
+    let second = state.add_override("launch_day".into(), 1500, 3000);
+    assert_eq!((first, second), (1, 2));
+
+    assert_eq!(state.active_override(999), None);
+    assert_eq!(state.active_override(1000).unwrap().item, "announcement");
+    // The latest started override wins while they overlap
+    assert_eq!(state.active_override(1500).unwrap().item, "launch_day");
+
+    let expired = state.expire_overrides(2000);
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].id, first);
+    assert_eq!(state.active_override(2999).unwrap().id, second);
+
+    // Ids continue after the highest pending one
+    assert_eq!(state.add_override("again".into(), 4000, 5000), 3);
+    state.expire_overrides(5000);
+    assert!(state.overrides.is_empty());
+}
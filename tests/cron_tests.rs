@@ -0,0 +1,121 @@
+use chrono::{Duration, Local, TimeZone};
+use expression::utils::cron::CronSchedule;
+
+// █▀█ ▄▀█ █▀█ █▀ █▀▀
+// █▀▀ █▀█ █▀▄ ▄█ ██▄
+
+#[test]
+fn test_cron_parse_valid() {
+    for expression in [
+        "* * * * *",
+        "0 9 * * 1-5",
+        "*/15 8-18/2 1,15 jan-jun mon,fri",
+        "30 23 * * 7",
+        "@daily",
+    ] {
+        assert!(
+            CronSchedule::parse(expression).is_ok(),
+            "Expected {} to parse",
+            expression
+        );
+    }
+}
+
+#[test]
+fn test_cron_parse_invalid() {
+    for expression in [
+        "",
+        "* * * *",
+        "60 * * * *",
+        "* 24 * * *",
+        "*/0 * * * *",
+        "5-1 * * * *",
+    ] {
+        assert!(
+            CronSchedule::parse(expression).is_err(),
+            "Expected {} to fail",
+            expression
+        );
+    }
+}
+
+// █▀▄▀█ ▄▀█ ▀█▀ █▀▀ █░█
+// █░▀░█ █▀█ ░█░ █▄▄ █▀█
+
+#[test]
+fn test_cron_matches_weekdays() {
+    let schedule = CronSchedule::parse("0 9 * * mon-fri").unwrap();
+
+    // 2025-03-31 is a Monday
+    assert!(schedule.matches(Local.with_ymd_and_hms(2025, 3, 31, 9, 0, 0).unwrap()));
+    assert!(!schedule.matches(Local.with_ymd_and_hms(2025, 3, 30, 9, 0, 0).unwrap()));
+    assert!(!schedule.matches(Local.with_ymd_and_hms(2025, 3, 31, 9, 1, 0).unwrap()));
+}
+
+#[test]
+fn test_cron_matches_sunday_alias() {
+    let schedule = CronSchedule::parse("0 0 * * 7").unwrap();
+
+    assert!(schedule.matches(Local.with_ymd_and_hms(2025, 3, 30, 0, 0, 0).unwrap()));
+}
+
+#[test]
+fn test_cron_matches_day_or_weekday() {
+    // Either the 1st of the month or any Friday
+    let schedule = CronSchedule::parse("0 0 1 * fri").unwrap();
+
+    assert!(schedule.matches(Local.with_ymd_and_hms(2025, 4, 1, 0, 0, 0).unwrap()));
+    assert!(schedule.matches(Local.with_ymd_and_hms(2025, 4, 4, 0, 0, 0).unwrap()));
+    assert!(!schedule.matches(Local.with_ymd_and_hms(2025, 4, 2, 0, 0, 0).unwrap()));
+}
+
+// █▄░█ █▀▀ ▀▄▀ ▀█▀
+// █░▀█ ██▄ █░█ ░█░
+
+#[test]
+fn test_cron_next_after_skips_weekend() {
+    let schedule = CronSchedule::parse("0 9 * * 1-5").unwrap();
+    // Saturday
+    let now = Local.with_ymd_and_hms(2025, 4, 5, 12, 0, 0).unwrap();
+
+    let next = schedule.next_after(now).unwrap();
+
+    assert_eq!(next, Local.with_ymd_and_hms(2025, 4, 7, 9, 0, 0).unwrap());
+}
+
+#[test]
+fn test_cron_next_after_is_strict() {
+    let schedule = CronSchedule::parse("*/15 * * * *").unwrap();
+    let now = Local.with_ymd_and_hms(2025, 4, 5, 12, 15, 0).unwrap();
+
+    let next = schedule.next_after(now).unwrap();
+
+    assert_eq!(next, Local.with_ymd_and_hms(2025, 4, 5, 12, 30, 0).unwrap());
+}
+
+#[test]
+fn test_cron_next_after_never() {
+    let schedule = CronSchedule::parse("0 0 30 2 *").unwrap();
+    let now = Local.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+
+    assert!(schedule.next_after(now).is_none());
+}
+
+#[test]
+fn test_cron_active_firing() {
+    let schedule = CronSchedule::parse("0 9 * * *").unwrap();
+    let duration = Duration::minutes(30);
+    let firing = Local.with_ymd_and_hms(2025, 4, 5, 9, 0, 0).unwrap();
+
+    assert_eq!(schedule.active_firing(firing, duration), Some(firing));
+    assert_eq!(
+        schedule.active_firing(firing + Duration::minutes(29), duration),
+        Some(firing)
+    );
+    assert!(schedule
+        .active_firing(firing + Duration::minutes(30), duration)
+        .is_none());
+    assert!(schedule
+        .active_firing(firing - Duration::seconds(1), duration)
+        .is_none());
+}
@@ -0,0 +1,111 @@
+use expression::core::crossfade::{get_frame, render_frame};
+use image::{ImageBuffer, Rgb, RgbImage};
+use std::error::Error;
+use std::path::Path;
+mod utils;
+use utils::{cleanup_test_dir, setup_test_dir};
+
+fn solid(path: &Path, width: u32, height: u32, color: [u8; 3]) {
+    let image: RgbImage = ImageBuffer::from_pixel(width, height, Rgb(color));
+    image.save(path).unwrap();
+}
+
+// █▀▀ █▀█ █▀█ █▀ █▀ █▀▀ ▄▀█ █▀▄ █▀▀
+// █▄▄ █▀▄ █▄█ ▄█ ▄█ █▀░ █▀█ █▄▀ ██▄
+
+#[test]
+fn test_render_frame_blends_and_resizes() -> Result<(), Box<dyn Error>> {
+    let test_dir = std::env::temp_dir().join("test_render_frame_blends_and_resizes");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &[], &[])?;
+    let (from, to, frame) = (
+        test_dir.join("06.png"),
+        test_dir.join("07.png"),
+        test_dir.join("frame.jpg"),
+    );
+    solid(&from, 8, 4, [0, 0, 0]);
+    solid(&to, 16, 8, [200, 100, 50]);
+
+    render_frame(&from, &to, 0.5, &frame)?;
+
+    let blended = image::open(&frame)?.to_rgb8();
+    assert_eq!(
+        blended.dimensions(),
+        (8, 4),
+        "Frame keeps the size of `from`"
+    );
+    let pixel = blended.get_pixel(4, 2);
+    // JPEG is lossy, allow a little drift
+    for (channel, expected) in pixel.0.iter().zip([100, 50, 25]) {
+        assert!(
+            channel.abs_diff(expected) <= 3,
+            "Unexpected pixel {:?}",
+            pixel
+        );
+    }
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
+
+#[test]
+fn test_get_frame_is_cached() -> Result<(), Box<dyn Error>> {
+    let test_dir = std::env::temp_dir().join("test_get_frame_is_cached");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &[], &["cache"])?;
+    let (from, to, cache) = (
+        test_dir.join("06.png"),
+        test_dir.join("07.png"),
+        test_dir.join("cache"),
+    );
+    solid(&from, 4, 4, [0, 0, 0]);
+    solid(&to, 4, 4, [255, 255, 255]);
+
+    let frame = get_frame(&cache, &from, &to, 3, 12)?;
+    assert!(frame.exists());
+    assert!(frame.ends_with("003.jpg"));
+
+    let modified = frame.metadata()?.modified()?;
+    let cached = get_frame(&cache, &from, &to, 3, 12)?;
+    assert_eq!(frame, cached);
+    assert_eq!(
+        modified,
+        cached.metadata()?.modified()?,
+        "Frame is not re-rendered"
+    );
+
+    // Different step count is a different set of frames
+    let other = get_frame(&cache, &from, &to, 3, 6)?;
+    assert_ne!(frame.parent(), other.parent());
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
+
+#[test]
+fn test_get_frame_prunes_other_pairs() -> Result<(), Box<dyn Error>> {
+    let test_dir = std::env::temp_dir().join("test_get_frame_prunes_other_pairs");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &[], &["cache"])?;
+    let (first, second, third, cache) = (
+        test_dir.join("06.png"),
+        test_dir.join("07.png"),
+        test_dir.join("08.png"),
+        test_dir.join("cache"),
+    );
+    solid(&first, 4, 4, [0, 0, 0]);
+    solid(&second, 4, 4, [128, 128, 128]);
+    solid(&third, 4, 4, [255, 255, 255]);
+
+    let old = get_frame(&cache, &first, &second, 1, 4)?;
+    let old_sibling = get_frame(&cache, &first, &second, 2, 4)?;
+    assert!(old.exists() && old_sibling.exists(), "Same pair is kept");
+
+    let new = get_frame(&cache, &second, &third, 1, 4)?;
+    assert!(new.exists());
+    assert!(!old.exists(), "Frames of the previous pair are removed");
+    assert_eq!(std::fs::read_dir(&cache)?.count(), 1);
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
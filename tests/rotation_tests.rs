@@ -0,0 +1,89 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use expression::config::{CollectionScheduleConfig, RotationPeriod};
+use expression::core::rotation::{next_switch, scheduled_collection};
+use std::collections::BTreeMap;
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn rotating(period: RotationPeriod) -> CollectionScheduleConfig {
+    CollectionScheduleConfig {
+        rotate: Some(period),
+        collections: vec!["Nature".into(), "Cities".into(), "Space".into()],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_weekly_rotation_switches_on_monday() {
+    let config = rotating(RotationPeriod::Weekly);
+    let sunday = date(2025, 3, 30);
+    let monday = date(2025, 3, 31);
+    assert_eq!(monday.weekday(), Weekday::Mon);
+
+    assert_eq!(
+        scheduled_collection(&config, date(2025, 3, 24)),
+        scheduled_collection(&config, sunday)
+    );
+    assert_ne!(
+        scheduled_collection(&config, sunday),
+        scheduled_collection(&config, monday)
+    );
+
+    let (switch, _) = next_switch(&config, date(2025, 3, 26)).unwrap();
+    assert_eq!(switch, monday);
+}
+
+#[test]
+fn test_rotation_cycles_through_all_collections() {
+    let config = rotating(RotationPeriod::Daily);
+    let start = date(2025, 3, 31);
+    let week: Vec<_> = start
+        .iter_days()
+        .take(6)
+        .map(|day| scheduled_collection(&config, day).unwrap())
+        .collect();
+
+    assert_eq!(week[0..3], week[3..6]);
+    for collection in ["Nature", "Cities", "Space"] {
+        assert!(week.contains(&collection));
+    }
+}
+
+#[test]
+fn test_month_collection_wins_over_rotation() {
+    let mut config = rotating(RotationPeriod::Monthly);
+    config.month_collections = BTreeMap::from([(12, "Holidays".to_string())]);
+
+    assert_eq!(
+        scheduled_collection(&config, date(2025, 12, 24)),
+        Some("Holidays")
+    );
+    assert_ne!(
+        scheduled_collection(&config, date(2025, 11, 30)),
+        Some("Holidays")
+    );
+    assert_eq!(
+        next_switch(&config, date(2025, 11, 15)),
+        Some((date(2025, 12, 1), Some("Holidays")))
+    );
+}
+
+#[test]
+fn test_month_only_schedule() {
+    let config = CollectionScheduleConfig {
+        month_collections: BTreeMap::from([(10, "Halloween".to_string())]),
+        ..Default::default()
+    };
+
+    assert_eq!(scheduled_collection(&config, date(2025, 9, 30)), None);
+    assert_eq!(
+        next_switch(&config, date(2025, 10, 20)),
+        Some((date(2025, 11, 1), None))
+    );
+
+    // Nothing scheduled never switches
+    let empty = CollectionScheduleConfig::default();
+    assert_eq!(next_switch(&empty, date(2025, 10, 20)), None);
+}
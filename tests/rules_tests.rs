@@ -0,0 +1,104 @@
+use chrono::{Local, TimeZone};
+use expression::core::rules::{first_match, Condition, Rule, SystemVariables, Value};
+use std::collections::BTreeMap;
+
+fn variables(name: &str) -> Value {
+    match name {
+        "battery" => Value::Number(15.0),
+        "charging" => Value::Bool(false),
+        "time" => Value::Number(9.0 * 60.0 + 45.0),
+        "weekday" => Value::Text("sat".into()),
+        "outputs" => Value::Number(2.0),
+        "env.XDG_CURRENT_DESKTOP" => Value::Text("Hyprland".into()),
+        _ => Value::Missing,
+    }
+}
+
+fn holds(condition: &str) -> bool {
+    Condition::parse(condition).unwrap().evaluate(&variables)
+}
+
+#[test]
+fn test_condition_operators() {
+    assert!(holds("battery < 20 && !charging"));
+    assert!(!holds("battery < 20 && charging"));
+    assert!(holds("battery >= 50 || outputs == 2"));
+    assert!(holds("!(battery > 20)"));
+    assert!(holds("weekday in [\"sat\", 'sun']"));
+    assert!(!holds("weekday in [\"mon\"]"));
+    assert!(holds("env.XDG_CURRENT_DESKTOP == \"hyprland\""));
+}
+
+#[test]
+fn test_condition_times() {
+    assert!(holds("time >= 09:30 && time < 10:00"));
+    assert!(!holds("time < 09:00"));
+}
+
+#[test]
+fn test_missing_values_never_match() {
+    // e.g. `battery` on a desktop
+    assert!(!holds("env.UNSET_VARIABLE == \"x\""));
+    assert!(!holds("env.UNSET_VARIABLE != \"x\""));
+    assert!(!holds("env.UNSET_VARIABLE"));
+}
+
+#[test]
+fn test_condition_parse_errors() {
+    for invalid in [
+        "",
+        "battery <",
+        "battery < 20 &&",
+        "(battery < 20",
+        "batery < 20",
+        "time > 25:00",
+        "exists(~/.vpn-on)",
+        "\"unclosed",
+        "battery ~ 20",
+    ] {
+        assert!(Condition::parse(invalid).is_err(), "parsed: {}", invalid);
+    }
+}
+
+#[test]
+fn test_condition_probes() {
+    let condition = Condition::parse("probe.vpn && !probe.meeting || hour > 9").unwrap();
+    assert_eq!(condition.probes(), vec!["vpn", "meeting"]);
+}
+
+#[test]
+fn test_first_match_in_order() {
+    let rule = |name: &str, when: &str| Rule {
+        name: name.into(),
+        condition: Condition::parse(when).unwrap(),
+        item: name.into(),
+    };
+    let rules = vec![
+        rule("docked", "outputs > 2"),
+        rule("low_power", "battery < 20"),
+        rule("weekend", "weekday in [\"sat\", \"sun\"]"),
+    ];
+
+    let matched = first_match(&rules, &variables).unwrap();
+    assert_eq!(matched.name, "low_power");
+}
+
+#[test]
+fn test_system_variables() {
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 14, 5, 0).unwrap();
+    let probes = BTreeMap::from([
+        ("yes".to_string(), "exit 0".to_string()),
+        ("no".to_string(), "exit 3".to_string()),
+    ]);
+    let system = SystemVariables::new(now, &probes);
+
+    assert_eq!(system.get("time"), Value::Number(845.0));
+    assert_eq!(system.get("weekday"), Value::Text("mon".into()));
+    assert_eq!(system.get("date"), Value::Text("03-31".into()));
+    assert_eq!(system.get("probe.yes"), Value::Bool(true));
+    assert_eq!(system.get("probe.no"), Value::Bool(false));
+    assert_eq!(system.get("probe.undefined"), Value::Bool(false));
+
+    let condition = Condition::parse("exists(\"/\") && !exists(\"/no/such/path\")").unwrap();
+    assert!(condition.evaluate(&|name| system.get(name)));
+}
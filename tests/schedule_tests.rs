@@ -0,0 +1,429 @@
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use expression::config::{Layer, SpecialEntryConfig, DEFAULT_LAYERS};
+use expression::core::schedule::{
+    next_activation, next_boundary, parse_special_entries, resolve_special, Precedence,
+};
+use expression::utils::solar::{event_time, Location, SolarEvent};
+use std::collections::BTreeMap;
+
+fn entries(pairs: &[(&str, &str)]) -> BTreeMap<String, SpecialEntryConfig> {
+    pairs
+        .iter()
+        .map(|(key, item)| (key.to_string(), SpecialEntryConfig::Item(item.to_string())))
+        .collect()
+}
+
+fn cron_entry(cron: &str, duration: Option<&str>) -> SpecialEntryConfig {
+    SpecialEntryConfig::Cron {
+        cron: cron.to_string(),
+        item: None,
+        duration: duration.map(String::from),
+    }
+}
+
+// █▀█ ▄▀█ █▀█ █▀ █▀▀
+// █▀▀ █▀█ █▀▄ ▄█ ██▄
+
+#[test]
+fn test_parse_special_entries_valid_keys() {
+    let raw = entries(&[
+        ("5", "wakeup"),
+        ("12-25", "christmas"),
+        ("--01-01", "new_year"),
+        ("2026-11-01", "release"),
+        ("12-20..01-05", "holidays"),
+        ("12-25 09", "presents"),
+        ("sunset-30m", "dusk"),
+        ("12-25 sunrise", "christmas_morning"),
+        ("12:00-14:00", "lunch"),
+        ("22:30-01:15", "sleep_time"),
+        ("--12-14", "birthday"),
+        ("12-24 18:00-24:00", "christmas_eve"),
+    ]);
+
+    let parsed = parse_special_entries(&raw).unwrap();
+
+    assert_eq!(parsed.len(), 12);
+}
+
+#[test]
+fn test_parse_special_entries_invalid_keys() {
+    for key in [
+        "24",
+        "13-01",
+        "02-30",
+        "2026-01-05..2026-01-01",
+        "12-20..2026-12-31",
+        "moon:blue",
+        "a b c",
+    ] {
+        let raw = entries(&[(key, "item")]);
+        assert!(
+            parse_special_entries(&raw).is_err(),
+            "Expected error for {}",
+            key
+        );
+    }
+}
+
+// █▀█ █▀▀ █▀ █▀█ █░░ █░█ █▀▀
+// █▀▄ ██▄ ▄█ █▄█ █▄▄ ▀▄▀ ██▄
+
+#[test]
+fn test_resolve_special_hourly() {
+    let parsed = parse_special_entries(&entries(&[("23", "sleep")])).unwrap();
+
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 23, 15, 0).unwrap();
+    assert_eq!(resolve_special(&parsed, now, None).unwrap().item, "sleep");
+
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 22, 59, 59).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_none());
+}
+
+#[test]
+fn test_resolve_special_dated_over_hourly() {
+    let parsed =
+        parse_special_entries(&entries(&[("9", "workout"), ("12-25", "christmas")])).unwrap();
+    let now = Local.with_ymd_and_hms(2025, 12, 25, 9, 0, 0).unwrap();
+
+    let entry = resolve_special(&parsed, now, None).unwrap();
+
+    assert_eq!(entry.item, "christmas");
+    assert_eq!(entry.precedence(), Precedence::Dated);
+}
+
+#[test]
+fn test_resolve_special_dated_hourly_over_dated() {
+    let parsed =
+        parse_special_entries(&entries(&[("12-25", "christmas"), ("12-25 9", "presents")]))
+            .unwrap();
+
+    let now = Local.with_ymd_and_hms(2025, 12, 25, 9, 30, 0).unwrap();
+    assert_eq!(
+        resolve_special(&parsed, now, None).unwrap().item,
+        "presents"
+    );
+
+    let now = Local.with_ymd_and_hms(2025, 12, 25, 10, 0, 0).unwrap();
+    assert_eq!(
+        resolve_special(&parsed, now, None).unwrap().item,
+        "christmas"
+    );
+}
+
+#[test]
+fn test_resolve_special_narrower_date_wins() {
+    let parsed = parse_special_entries(&entries(&[
+        ("12-20..12-31", "holidays"),
+        ("12-25", "christmas"),
+    ]))
+    .unwrap();
+
+    let now = Local.with_ymd_and_hms(2025, 12, 25, 12, 0, 0).unwrap();
+    assert_eq!(
+        resolve_special(&parsed, now, None).unwrap().item,
+        "christmas"
+    );
+
+    let now = Local.with_ymd_and_hms(2025, 12, 28, 12, 0, 0).unwrap();
+    assert_eq!(
+        resolve_special(&parsed, now, None).unwrap().item,
+        "holidays"
+    );
+}
+
+#[test]
+fn test_resolve_special_range_wraps_new_year() {
+    let parsed = parse_special_entries(&entries(&[("12-20..01-05", "holidays")])).unwrap();
+
+    let now = Local.with_ymd_and_hms(2026, 1, 3, 8, 0, 0).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_some());
+
+    let now = Local.with_ymd_and_hms(2026, 1, 6, 8, 0, 0).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_none());
+}
+
+#[test]
+fn test_resolve_special_fixed_date() {
+    let parsed = parse_special_entries(&entries(&[("2026-11-01", "release")])).unwrap();
+
+    let now = Local.with_ymd_and_hms(2026, 11, 1, 0, 0, 0).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_some());
+
+    let now = Local.with_ymd_and_hms(2027, 11, 1, 0, 0, 0).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_none());
+}
+
+#[test]
+fn test_resolve_special_solar() {
+    let london = Location {
+        latitude: 51.5074,
+        longitude: -0.1278,
+    };
+    let parsed = parse_special_entries(&entries(&[("sunset-30m", "dusk")])).unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 6, 21).unwrap();
+    let sunset = event_time(SolarEvent::Sunset, date, &london)
+        .unwrap()
+        .with_timezone(&Local);
+
+    let now = sunset - Duration::minutes(20);
+    assert_eq!(
+        resolve_special(&parsed, now, Some(&london)).unwrap().item,
+        "dusk"
+    );
+    assert!(resolve_special(&parsed, now, None).is_none());
+
+    let now = sunset - Duration::minutes(40);
+    assert!(resolve_special(&parsed, now, Some(&london)).is_none());
+}
+
+#[test]
+fn test_resolve_special_astro() {
+    let parsed = parse_special_entries(&entries(&[
+        ("moon:full", "full_moon"),
+        ("moon:full 22", "moonlight"),
+        ("meteor:geminids", "geminids"),
+    ]))
+    .unwrap();
+
+    let now = Local.with_ymd_and_hms(2025, 1, 13, 12, 0, 0).unwrap();
+    assert_eq!(
+        resolve_special(&parsed, now, None).unwrap().item,
+        "full_moon"
+    );
+
+    let now = Local.with_ymd_and_hms(2025, 1, 13, 22, 30, 0).unwrap();
+    assert_eq!(
+        resolve_special(&parsed, now, None).unwrap().item,
+        "moonlight"
+    );
+
+    // Single day peak is narrower than the full moon
+    let now = Local.with_ymd_and_hms(2024, 12, 14, 12, 0, 0).unwrap();
+    assert_eq!(
+        resolve_special(&parsed, now, None).unwrap().item,
+        "geminids"
+    );
+
+    let now = Local.with_ymd_and_hms(2025, 1, 21, 12, 0, 0).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_none());
+}
+
+#[test]
+fn test_resolve_special_cron() {
+    let mut raw = entries(&[("9", "workout")]);
+    raw.insert("standup".into(), cron_entry("0 9 * * 1-5", Some("15m")));
+    let parsed = parse_special_entries(&raw).unwrap();
+
+    // Monday
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 9, 10, 0).unwrap();
+    assert_eq!(resolve_special(&parsed, now, None).unwrap().item, "standup");
+
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 9, 15, 0).unwrap();
+    assert_eq!(resolve_special(&parsed, now, None).unwrap().item, "workout");
+
+    // Sunday
+    let now = Local.with_ymd_and_hms(2025, 3, 30, 9, 10, 0).unwrap();
+    assert_eq!(resolve_special(&parsed, now, None).unwrap().item, "workout");
+}
+
+#[test]
+fn test_parse_special_entries_invalid_cron() {
+    let mut raw = BTreeMap::new();
+    raw.insert("standup".to_string(), cron_entry("0 9 * *", None));
+    assert!(parse_special_entries(&raw).is_err());
+
+    raw.insert("standup".to_string(), cron_entry("0 9 * * *", Some("soon")));
+    assert!(parse_special_entries(&raw).is_err());
+}
+
+#[test]
+fn test_next_boundary_cron() {
+    let mut raw = BTreeMap::new();
+    raw.insert("standup".to_string(), cron_entry("0 9 * * *", Some("15m")));
+    let parsed = parse_special_entries(&raw).unwrap();
+
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 8, 20, 0).unwrap();
+    let expected = Local.with_ymd_and_hms(2025, 3, 31, 9, 0, 0).unwrap();
+    assert_eq!(next_boundary(&parsed, now, None), Some(expected));
+
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 9, 5, 0).unwrap();
+    let expected = Local.with_ymd_and_hms(2025, 3, 31, 9, 15, 0).unwrap();
+    assert_eq!(next_boundary(&parsed, now, None), Some(expected));
+}
+
+// █▀█ ▄▀█ █▄░█ █▀▀ █▀▀
+// █▀▄ █▀█ █░▀█ █▄█ ██▄
+
+#[test]
+fn test_parse_special_entries_invalid_ranges() {
+    for key in [
+        "12:00-12:00",
+        "24:00-02:00",
+        "12:5-14:00",
+        "12:60-14:00",
+        "123:00-14:00",
+    ] {
+        let raw = entries(&[(key, "item")]);
+        assert!(
+            parse_special_entries(&raw).is_err(),
+            "Expected error for {}",
+            key
+        );
+    }
+}
+
+#[test]
+fn test_ambiguous_keys_are_rejected() {
+    for key in ["12-14", "07-04", "1-24"] {
+        let err = parse_special_entries(&entries(&[(key, "item")])).unwrap_err();
+        assert!(
+            err.to_string().contains("ambiguous key"),
+            "Unexpected error for {}: {}",
+            key,
+            err
+        );
+    }
+    let err = parse_special_entries(&entries(&[("12-14", "lunch")])).unwrap_err();
+    assert!(err.to_string().contains("`--12-14`"));
+    assert!(err.to_string().contains("`12:00-14:00`"));
+
+    // Dates with a day past 24, the `--` form and date parts of `DATE TIME` stay dates
+    let parsed = parse_special_entries(&entries(&[
+        ("--07-04", "independence"),
+        ("12-25", "christmas"),
+        ("12-14 09", "birthday"),
+    ]))
+    .unwrap();
+    assert!(parsed
+        .iter()
+        .all(|entry| entry.precedence() != Precedence::Hourly));
+
+    let now = Local.with_ymd_and_hms(2025, 7, 4, 10, 0, 0).unwrap();
+    assert_eq!(
+        resolve_special(&parsed, now, None).unwrap().item,
+        "independence"
+    );
+}
+
+#[test]
+fn test_parse_special_entries_overlapping_ranges() {
+    let raw = entries(&[("12:00-14:00", "lunch"), ("13:30-15:00", "nap")]);
+    assert!(parse_special_entries(&raw).is_err());
+
+    let raw = entries(&[("22:00-06:00", "sleep"), ("05", "wakeup")]);
+    assert!(parse_special_entries(&raw).is_err());
+
+    // Touching ranges don't overlap
+    let raw = entries(&[("12:00-14:00", "lunch"), ("14:00-15:00", "nap")]);
+    assert!(parse_special_entries(&raw).is_ok());
+
+    // Different dates don't overlap
+    let raw = entries(&[("12:00-14:00", "lunch"), ("12-25 12:00-14:00", "feast")]);
+    assert!(parse_special_entries(&raw).is_ok());
+}
+
+#[test]
+fn test_resolve_special_range() {
+    let parsed = parse_special_entries(&entries(&[("22:30-23:15", "sleep_time")])).unwrap();
+
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 22, 30, 0).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_some());
+
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 23, 14, 59).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_some());
+
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 23, 15, 0).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_none());
+}
+
+#[test]
+fn test_resolve_special_range_wraps_midnight() {
+    let parsed = parse_special_entries(&entries(&[("12-31 22:00-02:00", "party")])).unwrap();
+
+    let now = Local.with_ymd_and_hms(2025, 12, 31, 23, 0, 0).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_some());
+
+    // Belongs to the day the range started
+    let now = Local.with_ymd_and_hms(2026, 1, 1, 1, 0, 0).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_some());
+
+    let now = Local.with_ymd_and_hms(2025, 12, 31, 1, 0, 0).unwrap();
+    assert!(resolve_special(&parsed, now, None).is_none());
+}
+
+#[test]
+fn test_next_boundary_range() {
+    let parsed = parse_special_entries(&entries(&[("22:30-06:00", "sleep_time")])).unwrap();
+
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 21, 0, 0).unwrap();
+    let expected = Local.with_ymd_and_hms(2025, 3, 31, 22, 30, 0).unwrap();
+    assert_eq!(next_boundary(&parsed, now, None), Some(expected));
+
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 23, 0, 0).unwrap();
+    let expected = Local.with_ymd_and_hms(2025, 4, 1, 6, 0, 0).unwrap();
+    assert_eq!(next_boundary(&parsed, now, None), Some(expected));
+}
+
+// █░█░█ █ █▄░█ █▀▄ █▀█ █░█░█
+// ▀▄▀▄▀ █ █░▀█ █▄▀ █▄█ ▀▄▀▄▀
+
+#[test]
+fn test_active_window_range_started_yesterday() {
+    let parsed = parse_special_entries(&entries(&[("22:30-06:00", "sleep_time")])).unwrap();
+    let now = Local.with_ymd_and_hms(2025, 4, 1, 2, 0, 0).unwrap();
+
+    let (start, end) = parsed[0].active_window(now, None).unwrap();
+
+    assert_eq!(
+        start,
+        Local.with_ymd_and_hms(2025, 3, 31, 22, 30, 0).unwrap()
+    );
+    assert_eq!(end, Local.with_ymd_and_hms(2025, 4, 1, 6, 0, 0).unwrap());
+}
+
+#[test]
+fn test_next_activation_skips_other_dates() {
+    let parsed =
+        parse_special_entries(&entries(&[("23", "sleep"), ("04-01 12", "fools")])).unwrap();
+
+    let now = Local.with_ymd_and_hms(2025, 3, 31, 22, 0, 0).unwrap();
+    let expected = Local.with_ymd_and_hms(2025, 3, 31, 23, 0, 0).unwrap();
+    assert_eq!(next_activation(&parsed, now, None), Some(expected));
+
+    let now = Local.with_ymd_and_hms(2025, 4, 1, 9, 0, 0).unwrap();
+    let expected = Local.with_ymd_and_hms(2025, 4, 1, 12, 0, 0).unwrap();
+    assert_eq!(next_activation(&parsed, now, None), Some(expected));
+}
+
+#[test]
+fn test_hourly_layer_can_win_over_dated_entries() {
+    let parsed =
+        parse_special_entries(&entries(&[("12-25", "christmas"), ("09", "morning")])).unwrap();
+    let (hourly, dated): (Vec<_>, Vec<_>) = parsed
+        .into_iter()
+        .partition(|entry| entry.precedence() == Precedence::Hourly);
+    let now = Local.with_ymd_and_hms(2025, 12, 25, 9, 30, 0).unwrap();
+
+    assert_eq!(
+        resolve_special(&dated, now, None).unwrap().item,
+        "christmas"
+    );
+    assert_eq!(resolve_special(&hourly, now, None).unwrap().item, "morning");
+}
+
+#[test]
+fn test_layers_parse_in_order() {
+    #[derive(serde::Deserialize)]
+    struct General {
+        layers: Vec<Layer>,
+    }
+
+    let general: General = toml::from_str(r#"layers = ["root", "collection", "gap"]"#).unwrap();
+    assert_eq!(
+        general.layers,
+        vec![Layer::Root, Layer::Collection, Layer::Gap]
+    );
+    assert_eq!(DEFAULT_LAYERS[0].to_string(), "session");
+    assert!(toml::from_str::<General>(r#"layers = ["weather"]"#).is_err());
+}
@@ -0,0 +1,83 @@
+use chrono::{TimeZone, Utc};
+use chrono_tz::{America::New_York, Pacific::Auckland};
+use expression::utils::season::{get_season, Hemisphere, Season, SeasonBoundaries};
+
+// █▀ █▀▀ ▄▀█ █▀ █▀█ █▄░█
+// ▄█ ██▄ █▀█ ▄█ █▄█ █░▀█
+
+#[test]
+fn test_get_season_meteorological() {
+    let cases = [
+        (3, Season::Spring),
+        (5, Season::Spring),
+        (6, Season::Summer),
+        (9, Season::Autumn),
+        (11, Season::Autumn),
+        (12, Season::Winter),
+        (2, Season::Winter),
+    ];
+    for (month, expected) in cases {
+        let time = Utc.with_ymd_and_hms(2025, month, 1, 0, 0, 0).unwrap();
+        let season = get_season(time, &SeasonBoundaries::Meteorological, &Hemisphere::North);
+        assert_eq!(season, expected, "Unexpected season for month {}", month);
+    }
+}
+
+#[test]
+fn test_get_season_follows_local_date() {
+    let boundaries = SeasonBoundaries::Meteorological;
+
+    // Still May 31st in New York while it's already June in UTC
+    let evening = New_York.with_ymd_and_hms(2025, 5, 31, 22, 0, 0).unwrap();
+    assert_eq!(
+        get_season(evening, &boundaries, &Hemisphere::North),
+        Season::Spring
+    );
+
+    // Already June 1st in Auckland while it's still May in UTC
+    let morning = Auckland.with_ymd_and_hms(2025, 6, 1, 8, 0, 0).unwrap();
+    assert_eq!(
+        get_season(morning, &boundaries, &Hemisphere::South),
+        Season::Winter
+    );
+}
+
+#[test]
+fn test_get_season_astronomical() {
+    let boundaries = SeasonBoundaries::Astronomical;
+    let season = |month, day| {
+        let time = Utc.with_ymd_and_hms(2025, month, day, 12, 0, 0).unwrap();
+        get_season(time, &boundaries, &Hemisphere::North)
+    };
+
+    // March equinox: 2025-03-20
+    assert_eq!(season(3, 19), Season::Winter);
+    assert_eq!(season(3, 21), Season::Spring);
+    // June solstice: 2025-06-21
+    assert_eq!(season(6, 20), Season::Spring);
+    assert_eq!(season(6, 22), Season::Summer);
+    // December solstice: 2025-12-21
+    assert_eq!(season(12, 20), Season::Autumn);
+    assert_eq!(season(12, 22), Season::Winter);
+}
+
+#[test]
+fn test_get_season_southern_hemisphere() {
+    let time = Utc.with_ymd_and_hms(2025, 1, 15, 0, 0, 0).unwrap();
+
+    for boundaries in [
+        SeasonBoundaries::Meteorological,
+        SeasonBoundaries::Astronomical,
+    ] {
+        assert_eq!(
+            get_season(time, &boundaries, &Hemisphere::South),
+            Season::Summer
+        );
+    }
+}
+
+#[test]
+fn test_season_collection_names() {
+    assert!(Season::Autumn.collection_names().contains(&"fall"));
+    assert_eq!(Season::Winter.to_string(), "winter");
+}
@@ -0,0 +1,284 @@
+use chrono::NaiveDate;
+use expression::config::get_group_config;
+use expression::core::{
+    selection::{
+        daily_index, portable_key, sequential_index, shuffle_index, weight_of, weighted_index,
+    },
+    state::{self, SequentialPosition, Session, ShuffleBag, State},
+    wallpaper::WallpaperItem,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+mod utils;
+use utils::{cleanup_test_dir, setup_test_dir};
+
+fn wallpapers(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("/group/{}.jpg", i)).collect()
+}
+
+// █▀ █░█ █░█ █▀▀ █▀▀ █░░ █▀▀
+// ▄█ █▀█ █▄█ █▀░ █▀░ █▄▄ ██▄
+
+#[test]
+fn test_shuffle_shows_every_wallpaper_once_per_cycle() {
+    let group = wallpapers(5);
+    let mut bag = ShuffleBag::default();
+
+    for _ in 0..20 {
+        let cycle: HashSet<usize> = (0..5).map(|_| shuffle_index(&mut bag, &group)).collect();
+        assert_eq!(cycle.len(), 5, "Each cycle shows every wallpaper");
+    }
+}
+
+#[test]
+fn test_shuffle_never_repeats_at_wrap_point() {
+    let group = wallpapers(2);
+    let mut bag = ShuffleBag::default();
+
+    let mut previous = shuffle_index(&mut bag, &group);
+    for _ in 0..50 {
+        let index = shuffle_index(&mut bag, &group);
+        assert_ne!(index, previous, "Same wallpaper shown twice in a row");
+        previous = index;
+    }
+}
+
+#[test]
+fn test_shuffle_drops_removed_wallpapers() {
+    let mut group = wallpapers(4);
+    let mut bag = ShuffleBag::default();
+    shuffle_index(&mut bag, &group);
+
+    let removed = bag.remaining[0].clone();
+    group.retain(|wallpaper| *wallpaper != removed);
+    for _ in 0..6 {
+        let index = shuffle_index(&mut bag, &group);
+        assert_ne!(group[index], removed);
+    }
+}
+
+// █▀ █▀▀ █▀█ █░█ █▀▀ █▄░█ ▀█▀ █ ▄▀█ █░░
+// ▄█ ██▄ ▀▀█ █▄█ ██▄ █░▀█ ░█░ █ █▀█ █▄▄
+
+#[test]
+fn test_sequential_advances_once_per_activation() {
+    let group = wallpapers(3);
+    let mut position = SequentialPosition::default();
+    let day = 86400;
+
+    assert_eq!(sequential_index(&mut position, &group, 0), 0);
+    // Woken up again during the same activation
+    assert_eq!(sequential_index(&mut position, &group, 0), 0);
+
+    assert_eq!(sequential_index(&mut position, &group, day), 1);
+    assert_eq!(sequential_index(&mut position, &group, 2 * day), 2);
+    assert_eq!(
+        sequential_index(&mut position, &group, 3 * day),
+        0,
+        "Wraps around"
+    );
+}
+
+#[test]
+fn test_sequential_continues_after_removed_wallpaper() {
+    let mut group = wallpapers(4);
+    let mut position = SequentialPosition::default();
+    sequential_index(&mut position, &group, 0);
+    sequential_index(&mut position, &group, 1);
+    assert_eq!(position.last.as_deref(), Some("/group/1.jpg"));
+
+    group.remove(1);
+    let index = sequential_index(&mut position, &group, 2);
+    assert_eq!(group[index], "/group/2.jpg");
+}
+
+// █▀▄ ▄▀█ █ █░░ █▄█
+// █▄▀ █▀█ █ █▄▄ ░█░
+
+#[test]
+fn test_daily_index_is_deterministic() {
+    let group = wallpapers(10);
+    let date = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+
+    // Pinned, so the pick never changes between releases or machines
+    assert_eq!(daily_index(&group, date(31), "~/Pictures/07", ""), 1);
+    assert_eq!(daily_index(&group, date(31), "~/Pictures/07", "ours"), 8);
+
+    let picks: HashSet<usize> = (1..=31)
+        .map(|day| daily_index(&group, date(day), "~/Pictures/07", ""))
+        .collect();
+    assert!(picks.len() > 3, "Picks vary from day to day");
+}
+
+#[test]
+fn test_portable_key() {
+    let home = dirs::home_dir().unwrap();
+    let key = home.join("Pictures/07").display().to_string();
+    assert_eq!(portable_key(&key), "~/Pictures/07");
+    assert_eq!(portable_key("/srv/wallpapers/07"), "/srv/wallpapers/07");
+}
+
+// █░█░█ █▀▀ █ █▀▀ █░█ ▀█▀ █▀▀ █▀▄
+// ▀▄▀▄▀ ██▄ █ █▄█ █▀█ ░█░ ██▄ █▄▀
+
+#[test]
+fn test_weight_of_config_over_suffix() {
+    let weights = BTreeMap::from([("comet@3.jpg".to_string(), 7)]);
+    assert_eq!(weight_of("/group/comet@3.jpg", &weights), 7);
+    assert_eq!(weight_of("/group/comet@3.jpg", &BTreeMap::new()), 3);
+    assert_eq!(weight_of("/group/me@home.jpg", &BTreeMap::new()), 1);
+    assert_eq!(weight_of("/group/hidden@0.jpg", &BTreeMap::new()), 0);
+}
+
+#[test]
+fn test_weighted_index_distribution() {
+    let group = vec![
+        "/group/aurora.jpg".to_string(),
+        "/group/moon.jpg".to_string(),
+        "/group/hidden@0.jpg".to_string(),
+    ];
+    let weights = BTreeMap::from([("aurora.jpg".to_string(), 4)]);
+
+    let mut counts = [0; 3];
+    for _ in 0..5000 {
+        counts[weighted_index(&group, &weights)] += 1;
+    }
+    assert_eq!(counts[2], 0, "Weight 0 is never picked");
+    let ratio = counts[0] as f64 / counts[1] as f64;
+    assert!((3.0..5.0).contains(&ratio), "Unexpected ratio {}", ratio);
+
+    // All zero falls back to uniform
+    let hidden = vec!["/group/a@0.jpg".to_string(), "/group/b@0.jpg".to_string()];
+    assert!(weighted_index(&hidden, &BTreeMap::new()) < 2);
+}
+
+#[test]
+fn test_group_config_weights() -> io::Result<()> {
+    let test_dir = std::env::temp_dir().join("test_group_config_weights");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &[], &[])?;
+    std::fs::write(
+        test_dir.join("config.toml"),
+        "[general]\nselection_strategy = \"weighted\"\n\n[weights]\n\"aurora.jpg\" = 5\n",
+    )?;
+
+    let config = get_group_config(&vec![WallpaperItem::Group(test_dir.clone())]).unwrap();
+    assert_eq!(config.weights["aurora.jpg"], 5);
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
+
+#[test]
+fn test_group_config_interval() -> io::Result<()> {
+    let test_dir = std::env::temp_dir().join("test_group_config_interval");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &[], &[])?;
+    std::fs::write(
+        test_dir.join("config.toml"),
+        "[general]\ninterval_minutes = 10\n",
+    )?;
+
+    let config = get_group_config(&vec![WallpaperItem::Group(test_dir.clone())]).unwrap();
+    let general = config.general.unwrap();
+    assert_eq!(general.interval_minutes, Some(10));
+    assert!(
+        general.selection_strategy.is_none(),
+        "Strategy is left to the schedule"
+    );
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
+
+// █▀ ▀█▀ ▄▀█ ▀█▀ █▀▀
+// ▄█ ░█░ █▀█ ░█░ ██▄
+
+#[test]
+fn test_state_round_trip() -> io::Result<()> {
+    let test_dir = std::env::temp_dir().join("test_state_round_trip");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &[], &[])?;
+    let path = test_dir.join("nested/state.toml");
+
+    // Missing file starts fresh
+    assert!(State::load(&path).shuffle.is_empty());
+
+    let group = wallpapers(3);
+    let mut state = State::default();
+    let bag = state.shuffle.entry("/group".into()).or_default();
+    shuffle_index(bag, &group);
+    state.save(&path).unwrap();
+
+    let loaded = State::load(&path);
+    assert_eq!(loaded.shuffle["/group"].remaining.len(), 2);
+    assert_eq!(loaded.shuffle["/group"].last, state.shuffle["/group"].last);
+
+    // Runtime collection survives, clearing it removes the key
+    state.collection = Some("Nature".into());
+    state.save(&path).unwrap();
+    assert_eq!(State::load(&path).collection.as_deref(), Some("Nature"));
+    state.collection = None;
+    state.save(&path).unwrap();
+    assert_eq!(State::load(&path).collection, None);
+
+    // Sessions and overrides survive restarts
+    state.session = Some(Session {
+        start: 1000,
+        rounds: 4,
+        focus: 1500,
+        rest: 300,
+        focus_item: "focus".into(),
+        break_item: "break".into(),
+    });
+    state.add_override("launch_day".into(), 2000, 5600);
+    state.save(&path).unwrap();
+    let loaded = State::load(&path);
+    assert_eq!(loaded.session, state.session);
+    assert_eq!(loaded.overrides, state.overrides);
+
+    // Invalid file starts fresh too
+    std::fs::write(&path, "shuffle = 5")?;
+    assert!(State::load(&path).shuffle.is_empty());
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
+
+#[test]
+fn test_state_update_keeps_concurrent_changes() -> io::Result<()> {
+    let test_dir = std::env::temp_dir().join("test_state_update_keeps_concurrent_changes");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &[], &[])?;
+    let path = test_dir.join("state.toml");
+
+    // A command holds the lock while it writes an override
+    let lock = state::lock(&path).unwrap();
+    let writer = {
+        let path = path.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            let mut state = State::load(&path);
+            state.add_override("launch_day".into(), 2000, 5600);
+            state.save(&path).unwrap();
+            drop(lock);
+        })
+    };
+
+    // The daemon waits for it, then merges its shuffle progress into the latest state
+    let group = wallpapers(3);
+    State::update(&path, |state| {
+        shuffle_index(state.shuffle.entry("/group".into()).or_default(), &group)
+    })
+    .unwrap();
+    writer.join().unwrap();
+
+    let state = State::load(&path);
+    assert_eq!(state.overrides.len(), 1, "Override is not lost");
+    assert_eq!(state.shuffle["/group"].remaining.len(), 2);
+    let leftovers: Vec<_> = std::fs::read_dir(&test_dir)?.flatten().collect();
+    assert_eq!(leftovers.len(), 1, "No lock or partial files left behind");
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use expression::utils::solar::{event_time, Location, SolarAnchor, SolarEvent};
+
+const LONDON: Location = Location {
+    latitude: 51.5074,
+    longitude: -0.1278,
+};
+
+const NEW_YORK: Location = Location {
+    latitude: 40.7128,
+    longitude: -74.0060,
+};
+
+fn assert_close(actual: Option<DateTime<Utc>>, expected: DateTime<Utc>) {
+    let actual = actual.expect("Expected a solar event");
+    let diff = (actual - expected).num_seconds().abs();
+    assert!(diff <= 180, "{} is not close to {}", actual, expected);
+}
+
+// █▀ █▀█ █░░ ▄▀█ █▀█   █▀▀ █░█ █▀▀ █▄░█ ▀█▀
+// ▄█ █▄█ █▄▄ █▀█ █▀▄   ██▄ ▀▄▀ ██▄ █░▀█ ░█░
+
+#[test]
+fn test_event_time_london_summer_solstice() {
+    let date = NaiveDate::from_ymd_opt(2025, 6, 21).unwrap();
+
+    let sunrise = event_time(SolarEvent::Sunrise, date, &LONDON);
+    let noon = event_time(SolarEvent::SolarNoon, date, &LONDON);
+    let sunset = event_time(SolarEvent::Sunset, date, &LONDON);
+
+    assert_close(
+        sunrise,
+        Utc.with_ymd_and_hms(2025, 6, 21, 3, 43, 0).unwrap(),
+    );
+    assert_close(noon, Utc.with_ymd_and_hms(2025, 6, 21, 12, 2, 0).unwrap());
+    assert_close(
+        sunset,
+        Utc.with_ymd_and_hms(2025, 6, 21, 20, 21, 0).unwrap(),
+    );
+}
+
+#[test]
+fn test_event_time_new_york_winter_solstice() {
+    let date = NaiveDate::from_ymd_opt(2025, 12, 21).unwrap();
+
+    let sunrise = event_time(SolarEvent::Sunrise, date, &NEW_YORK);
+    let sunset = event_time(SolarEvent::Sunset, date, &NEW_YORK);
+
+    assert_close(
+        sunrise,
+        Utc.with_ymd_and_hms(2025, 12, 21, 12, 16, 0).unwrap(),
+    );
+    assert_close(
+        sunset,
+        Utc.with_ymd_and_hms(2025, 12, 21, 21, 32, 0).unwrap(),
+    );
+}
+
+#[test]
+fn test_event_time_civil_twilight_brackets_sun() {
+    let date = NaiveDate::from_ymd_opt(2025, 3, 20).unwrap();
+
+    let dawn = event_time(SolarEvent::CivilDawn, date, &LONDON).unwrap();
+    let sunrise = event_time(SolarEvent::Sunrise, date, &LONDON).unwrap();
+    let sunset = event_time(SolarEvent::Sunset, date, &LONDON).unwrap();
+    let dusk = event_time(SolarEvent::CivilDusk, date, &LONDON).unwrap();
+
+    assert!(dawn < sunrise && sunset < dusk);
+}
+
+#[test]
+fn test_event_time_polar_day() {
+    let tromso = Location {
+        latitude: 69.6492,
+        longitude: 18.9553,
+    };
+    let date = NaiveDate::from_ymd_opt(2025, 6, 21).unwrap();
+
+    assert!(event_time(SolarEvent::Sunset, date, &tromso).is_none());
+}
+
+// ▄▀█ █▄░█ █▀▀ █░█ █▀█ █▀█
+// █▀█ █░▀█ █▄▄ █▀█ █▄█ █▀▄
+
+#[test]
+fn test_solar_anchor_parse() {
+    let anchor = SolarAnchor::parse("sunset-30m").unwrap();
+    assert_eq!(anchor.event, SolarEvent::Sunset);
+    assert_eq!(anchor.offset_seconds, -1800);
+
+    let anchor = SolarAnchor::parse("civil_dusk+1h30m").unwrap();
+    assert_eq!(anchor.event, SolarEvent::CivilDusk);
+    assert_eq!(anchor.offset_seconds, 5400);
+
+    assert_eq!(SolarAnchor::parse("sunrise").unwrap().offset_seconds, 0);
+    assert_eq!(
+        SolarAnchor::parse("sunset-30m").unwrap().to_string(),
+        "sunset-30m"
+    );
+}
+
+#[test]
+fn test_solar_anchor_parse_invalid() {
+    for name in ["moonrise", "sunset-", "sunset-30x", "sunset+soon", "12"] {
+        assert!(
+            SolarAnchor::parse(name).is_none(),
+            "Expected None for {}",
+            name
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use expression::config::{GroupSelectionStrategy, TimelineConfig};
+use expression::core::{
+    timeline::{get_timeline_items, natural_cmp, sequence_strategy},
+    wallpaper::WallpaperItem,
+};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+mod utils;
+use utils::{cleanup_test_dir, setup_test_dir};
+
+fn timeline(start: u8, end: Option<u8>, slots: &[u8], order: &[&str]) -> TimelineConfig {
+    TimelineConfig {
+        enabled: true,
+        order: order.iter().map(|name| name.to_string()).collect(),
+        start,
+        end,
+        slots: slots.to_vec(),
+    }
+}
+
+/// File names of the item at each hour
+fn names(items: &BTreeMap<u8, Vec<WallpaperItem>>) -> BTreeMap<u8, Vec<String>> {
+    let name = |path: &Path| path.file_name().unwrap().to_string_lossy().into_owned();
+    items
+        .iter()
+        .map(|(hour, item)| {
+            let names = match &item[0] {
+                WallpaperItem::Entry(path) | WallpaperItem::Group(path) => vec![name(path)],
+                WallpaperItem::Sequence(paths) => paths.iter().map(|path| name(path)).collect(),
+            };
+            (*hour, names)
+        })
+        .collect()
+}
+
+// ▀█▀ █ █▀▄▀█ █▀▀ █░░ █ █▄░█ █▀▀
+// ░█░ █ █░▀░█ ██▄ █▄▄ █ █░▀█ ██▄
+
+#[test]
+fn test_natural_cmp() {
+    assert_eq!(natural_cmp("img2.jpg", "img10.jpg"), Ordering::Less);
+    assert_eq!(natural_cmp("img010.jpg", "img9.jpg"), Ordering::Greater);
+    assert_eq!(natural_cmp("Dawn.jpg", "dusk.jpg"), Ordering::Less);
+    assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+}
+
+#[test]
+fn test_timeline_even_slots() -> io::Result<()> {
+    let test_dir = std::env::temp_dir().join("test_timeline_even_slots");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &["img1.jpg", "img2.jpg", "img10.jpg"], &[])?;
+
+    let config = timeline(6, Some(18), &[], &[]);
+    let items = get_timeline_items(test_dir.to_str().unwrap(), &["jpg"], &config)?;
+    let names = names(&items);
+
+    assert_eq!(items.len(), 12, "Expected hours 06 to 17");
+    assert_eq!(names[&6], ["img1.jpg"]);
+    assert_eq!(names[&9], ["img1.jpg"]);
+    assert_eq!(names[&10], ["img2.jpg"]);
+    assert_eq!(names[&14], ["img10.jpg"]);
+    assert_eq!(names[&17], ["img10.jpg"]);
+    assert!(!items.contains_key(&18));
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
+
+#[test]
+fn test_timeline_explicit_order_and_slots() -> io::Result<()> {
+    let test_dir = std::env::temp_dir().join("test_timeline_explicit_order_and_slots");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &["night.jpg", "day.jpg", "dusk.jpg"], &[])?;
+
+    // Window wraps past midnight
+    let config = timeline(
+        6,
+        Some(2),
+        &[6, 19, 22],
+        &["day.jpg", "dusk.jpg", "night.jpg"],
+    );
+    let items = get_timeline_items(test_dir.to_str().unwrap(), &["jpg"], &config)?;
+    let names = names(&items);
+
+    assert_eq!(names[&6], ["day.jpg"]);
+    assert_eq!(names[&18], ["day.jpg"]);
+    assert_eq!(names[&19], ["dusk.jpg"]);
+    assert_eq!(names[&23], ["night.jpg"]);
+    assert_eq!(names[&1], ["night.jpg"]);
+    assert!(!items.contains_key(&2));
+
+    let missing = timeline(0, None, &[], &["day.jpg", "noon.jpg"]);
+    assert!(get_timeline_items(test_dir.to_str().unwrap(), &["jpg"], &missing).is_err());
+
+    let mismatched = timeline(0, None, &[6, 12], &[]);
+    assert!(get_timeline_items(test_dir.to_str().unwrap(), &["jpg"], &mismatched).is_err());
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
+
+#[test]
+fn test_timeline_more_images_than_hours() -> io::Result<()> {
+    let test_dir = std::env::temp_dir().join("test_timeline_more_images_than_hours");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(
+        &test_dir,
+        &["1.jpg", "2.jpg", "3.jpg", "4.jpg", "5.jpg"],
+        &[],
+    )?;
+
+    let config = timeline(22, Some(0), &[], &[]);
+    let items = get_timeline_items(test_dir.to_str().unwrap(), &["jpg"], &config)?;
+    let names = names(&items);
+
+    assert_eq!(names[&22], ["1.jpg", "2.jpg", "3.jpg"]);
+    assert_eq!(names[&23], ["4.jpg", "5.jpg"]);
+
+    // Shared hours are spread in order, whatever the configured strategy
+    assert!(matches!(
+        sequence_strategy(&items[&22]),
+        Some(GroupSelectionStrategy::Spread)
+    ));
+    let entry = vec![WallpaperItem::Entry(test_dir.join("1.jpg"))];
+    assert!(sequence_strategy(&entry).is_none());
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
@@ -1,5 +1,8 @@
+use expression::config::GapPolicy;
 use expression::core::wallpaper;
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::PathBuf;
 mod utils;
 use utils::{cleanup_test_dir, setup_test_dir};
 
@@ -22,6 +25,81 @@ fn test_select_random_entry_valid() {
     cleanup_test_dir(&test_dir);
 }
 
+#[test]
+fn test_random_pool_hour_and_all() {
+    let test_dir = std::env::temp_dir().join("test_random_pool_hour_and_all");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &[], &["Nature/20"]).unwrap();
+    setup_test_dir(
+        &test_dir,
+        &[
+            "07.jpg",
+            "12.jpg",
+            "Nature/07.jpg",
+            "Nature/20/a.jpg",
+            "Nature/20/b.jpg",
+        ],
+        &[],
+    )
+    .unwrap();
+    let extensions = ["jpg"];
+    let root =
+        wallpaper::get_wallpaper_items(test_dir.to_str().unwrap(), &extensions, None).unwrap();
+    let nature = test_dir.join("Nature");
+    let nature =
+        wallpaper::get_wallpaper_items(nature.to_str().unwrap(), &extensions, None).unwrap();
+    let maps = [root, nature];
+
+    // Same hour across collections
+    let hour_pool = wallpaper::random_pool(&maps, Some(7), &extensions);
+    assert_eq!(hour_pool.len(), 2);
+    assert!(hour_pool.iter().all(|path| path.ends_with("07.jpg")));
+
+    // Every hour, groups expanded
+    let all_pool = wallpaper::random_pool(&maps, None, &extensions);
+    assert_eq!(all_pool.len(), 5);
+
+    // Hours without items give an empty pool
+    assert!(wallpaper::random_pool(&maps, Some(3), &extensions).is_empty());
+
+    cleanup_test_dir(&test_dir);
+}
+
+#[test]
+fn test_scheme_tagged_items() {
+    let test_dir = std::env::temp_dir().join("test_scheme_tagged_items");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(
+        &test_dir,
+        &["07.jpg", "07.dark.jpg", "07.light.jpg", "12.jpg"],
+        &["20.dark"],
+    )
+    .unwrap();
+    let dir = test_dir.to_str().unwrap();
+    let extensions = ["jpg"];
+
+    // Tagged items are ignored without a scheme, `20.dark/` is not `20/`
+    let plain = wallpaper::get_wallpaper_items(dir, &extensions, None).unwrap();
+    assert_eq!(plain.keys().copied().collect::<Vec<_>>(), vec![7, 12]);
+
+    let dark = wallpaper::get_scheme_items(dir, &extensions, Some("dark")).unwrap();
+    assert_eq!(dark.keys().copied().collect::<Vec<_>>(), vec![7, 12, 20]);
+    assert!(matches!(
+        &dark[&7][..],
+        [wallpaper::WallpaperItem::Entry(path)] if *path == test_dir.join("07.dark.jpg")
+    ));
+    assert!(matches!(dark[&20][0], wallpaper::WallpaperItem::Group(_)));
+
+    let light = wallpaper::get_scheme_items(dir, &extensions, Some("light")).unwrap();
+    assert!(matches!(
+        &light[&7][..],
+        [wallpaper::WallpaperItem::Entry(path)] if *path == test_dir.join("07.light.jpg")
+    ));
+    assert!(!light.contains_key(&20));
+
+    cleanup_test_dir(&test_dir);
+}
+
 #[test]
 fn test_select_random_entry_empty_dir() {
     let test_dir = std::env::temp_dir().join("test_select_random_entry_empty_dir");
@@ -66,3 +144,214 @@ fn test_select_wallpaper_with_direct_entry() {
 
     cleanup_test_dir(&test_dir);
 }
+
+// █▀▀ ▄▀█ █▀█   █▀█ █▀█ █░░ █ █▀▀ █▄█
+// █▄█ █▀█ █▀▀   █▀▀ █▄█ █▄▄ █ █▄▄ ░█░
+
+fn hourly_items(hours: &[u8]) -> BTreeMap<u8, Vec<wallpaper::WallpaperItem>> {
+    hours
+        .iter()
+        .map(|hour| {
+            let path = PathBuf::from(format!("{:02}.jpg", hour));
+            (*hour, vec![wallpaper::WallpaperItem::Entry(path)])
+        })
+        .collect()
+}
+
+#[test]
+fn test_resolve_gap_hour_exact_match() {
+    let items = hourly_items(&[6, 12, 20]);
+
+    for policy in [GapPolicy::Hold, GapPolicy::Next, GapPolicy::None] {
+        assert_eq!(wallpaper::resolve_gap_hour(&items, 12, &policy), Some(12));
+    }
+}
+
+#[test]
+fn test_resolve_gap_hour_hold() {
+    let items = hourly_items(&[6, 12, 20]);
+
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&items, 9, &GapPolicy::Hold),
+        Some(6)
+    );
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&items, 23, &GapPolicy::Hold),
+        Some(20)
+    );
+    // Wraps across midnight
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&items, 2, &GapPolicy::Hold),
+        Some(20)
+    );
+}
+
+#[test]
+fn test_resolve_gap_hour_next() {
+    let items = hourly_items(&[6, 12, 20]);
+
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&items, 9, &GapPolicy::Next),
+        Some(12)
+    );
+    // Wraps across midnight
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&items, 22, &GapPolicy::Next),
+        Some(6)
+    );
+}
+
+#[test]
+fn test_resolve_gap_hour_nearest() {
+    let items = hourly_items(&[6, 12, 20]);
+
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&items, 7, &GapPolicy::Nearest),
+        Some(6)
+    );
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&items, 11, &GapPolicy::Nearest),
+        Some(12)
+    );
+    // Tie goes to the earlier item
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&items, 9, &GapPolicy::Nearest),
+        Some(6)
+    );
+    // Across midnight: 01 is 5 hours from 20 and 5 hours from 06
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&items, 1, &GapPolicy::Nearest),
+        Some(20)
+    );
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&items, 2, &GapPolicy::Nearest),
+        Some(6)
+    );
+}
+
+#[test]
+fn test_resolve_gap_hour_random_and_none() {
+    let items = hourly_items(&[6, 12, 20]);
+
+    let hour = wallpaper::resolve_gap_hour(&items, 9, &GapPolicy::Random).unwrap();
+    assert!(items.contains_key(&hour));
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&items, 9, &GapPolicy::None),
+        None
+    );
+    assert_eq!(
+        wallpaper::resolve_gap_hour(&BTreeMap::new(), 9, &GapPolicy::Hold),
+        None
+    );
+}
+
+#[test]
+fn test_resolve_gap_span_hold() {
+    let items = hourly_items(&[6, 12, 20]);
+
+    // 12:00 to 20:00
+    assert_eq!(
+        wallpaper::resolve_gap_span(&items, 14, &GapPolicy::Hold),
+        (2, 5)
+    );
+    // 20:00 to 06:00, across midnight
+    assert_eq!(
+        wallpaper::resolve_gap_span(&items, 2, &GapPolicy::Hold),
+        (6, 3)
+    );
+}
+
+#[test]
+fn test_resolve_gap_span_other_policies() {
+    let items = hourly_items(&[6, 12, 20]);
+
+    // 07:00 to 12:00 shows the 12 item early
+    assert_eq!(
+        wallpaper::resolve_gap_span(&items, 9, &GapPolicy::Next),
+        (2, 3)
+    );
+    // Nothing replaces 12 until 20
+    assert_eq!(
+        wallpaper::resolve_gap_span(&items, 12, &GapPolicy::None),
+        (0, 7)
+    );
+    assert_eq!(
+        wallpaper::resolve_gap_span(&items, 9, &GapPolicy::Random),
+        (0, 0)
+    );
+}
+
+#[test]
+fn test_resolve_gap_span_none_lasts_until_next_item() {
+    let items = hourly_items(&[6, 12, 22]);
+
+    // Nothing replaces 22 until 06
+    assert_eq!(
+        wallpaper::resolve_gap_span(&items, 22, &GapPolicy::None),
+        (0, 7)
+    );
+    // Hours inside the span belong to 22 too
+    assert_eq!(
+        wallpaper::resolve_gap_span(&items, 23, &GapPolicy::None),
+        (1, 6)
+    );
+    assert_eq!(
+        wallpaper::resolve_gap_span(&items, 3, &GapPolicy::None),
+        (5, 2)
+    );
+}
+
+#[test]
+fn test_resolve_fill_hour_none_keeps_groups_and_fades() {
+    let mut items = hourly_items(&[6, 12]);
+    items.insert(
+        22,
+        vec![wallpaper::WallpaperItem::Group(PathBuf::from("22"))],
+    );
+
+    // A spread group keeps being selected from at 23
+    let hour = wallpaper::resolve_fill_hour(&items, 23, &GapPolicy::None, false);
+    assert_eq!(hour, Some(22));
+    assert!(matches!(
+        items[&hour.unwrap()].first(),
+        Some(wallpaper::WallpaperItem::Group(path)) if path.ends_with("22")
+    ));
+    // A plain entry is left as it is
+    assert_eq!(
+        wallpaper::resolve_fill_hour(&items, 14, &GapPolicy::None, false),
+        None
+    );
+    // Other policies resolve as usual
+    assert_eq!(
+        wallpaper::resolve_fill_hour(&items, 14, &GapPolicy::Next, false),
+        Some(22)
+    );
+}
+
+#[test]
+fn test_resolve_gap_span_single_item() {
+    let items = hourly_items(&[6]);
+
+    let (before, after) = wallpaper::resolve_gap_span(&items, 6, &GapPolicy::Hold);
+    assert_eq!(before + after + 1, 24);
+}
+
+#[test]
+fn test_crossfade_spans_gap_hours_under_none() {
+    let items = hourly_items(&[6, 12, 20]);
+
+    // 14:00 is inside the span of 12, so the fade from 12 towards 20 goes on
+    assert_eq!(
+        wallpaper::resolve_fill_hour(&items, 14, &GapPolicy::None, true),
+        Some(12)
+    );
+    assert_eq!(
+        wallpaper::resolve_gap_span(&items, 14, &GapPolicy::None),
+        (2, 5)
+    );
+    // Without a crossfade, the entry is left as it is
+    assert_eq!(
+        wallpaper::resolve_fill_hour(&items, 14, &GapPolicy::None, false),
+        None
+    );
+}
@@ -113,3 +113,57 @@ fn test_get_wallpaper_items_with_filter() -> io::Result<()> {
     cleanup_test_dir(&test_dir);
     Ok(())
 }
+
+#[test]
+fn test_find_collection_case_insensitive() -> io::Result<()> {
+    let test_dir = std::env::temp_dir().join("test_find_collection_case_insensitive");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &[], &["Summer", "Nature", "07"])?;
+
+    let found = wallpaper::find_collection(test_dir.to_str().unwrap(), &["summer"]);
+    assert!(found.unwrap().ends_with("Summer"));
+
+    let missing = wallpaper::find_collection(test_dir.to_str().unwrap(), &["autumn", "fall"]);
+    assert!(missing.is_none());
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
+
+#[test]
+fn test_overlay_items_per_hour_fallback() -> io::Result<()> {
+    let test_dir = std::env::temp_dir().join("test_overlay_items_per_hour_fallback");
+    cleanup_test_dir(&test_dir);
+    setup_test_dir(&test_dir, &[], &["winter"])?;
+    setup_test_dir(
+        &test_dir,
+        &["06.jpg", "12.jpg", "winter/12.jpg", "winter/20.jpg"],
+        &[],
+    )?;
+
+    let root = wallpaper::get_wallpaper_items(test_dir.to_str().unwrap(), &["jpg"], None)?;
+    let winter_dir = test_dir.join("winter");
+    let winter = wallpaper::get_wallpaper_items(winter_dir.to_str().unwrap(), &["jpg"], None)?;
+
+    let items = wallpaper::overlay_items(root, winter);
+
+    assert_eq!(items.len(), 3, "Expected hours 06, 12 and 20");
+    let path_of = |hour: u8| match &items[&hour][0] {
+        wallpaper::WallpaperItem::Entry(path) | wallpaper::WallpaperItem::Group(path) => {
+            path.clone()
+        }
+        wallpaper::WallpaperItem::Sequence(_) => unreachable!("No timeline in collections"),
+    };
+    assert!(
+        !path_of(6).starts_with(&winter_dir),
+        "06 falls back to root"
+    );
+    assert!(path_of(12).starts_with(&winter_dir), "12 is overridden");
+    assert!(
+        path_of(20).starts_with(&winter_dir),
+        "20 comes from the collection"
+    );
+
+    cleanup_test_dir(&test_dir);
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use chrono::{NaiveDate, TimeZone, Timelike, Utc};
+use expression::config::SpecialEntryConfig;
+use expression::core::schedule::{parse_special_entries, resolve_special};
+use expression::utils::{calc, zone::Zone};
+use std::collections::BTreeMap;
+
+// ▀█ █▀█ █▄░█ █▀▀
+// █▄ █▄█ █░▀█ ██▄
+
+#[test]
+fn test_parse_zone() {
+    assert!(Zone::parse("Asia/Tokyo").is_ok());
+    assert!(Zone::parse("America/New_York").is_ok());
+    assert!(Zone::parse("Mars/Olympus_Mons").is_err());
+    assert_eq!(Zone::default(), Zone::Local);
+}
+
+#[test]
+fn test_named_zone_follows_dst() {
+    let london = Zone::parse("Europe/London").unwrap();
+
+    let winter = Utc.with_ymd_and_hms(2025, 1, 15, 12, 0, 0).unwrap();
+    assert_eq!(winter.with_timezone(&london).hour(), 12);
+
+    let summer = Utc.with_ymd_and_hms(2025, 7, 15, 12, 0, 0).unwrap();
+    assert_eq!(summer.with_timezone(&london).hour(), 13);
+
+    // 01:30 is skipped when the clocks go forward
+    let gap = NaiveDate::from_ymd_opt(2025, 3, 30)
+        .unwrap()
+        .and_hms_opt(1, 30, 0)
+        .unwrap();
+    assert!(london.from_local_datetime(&gap).earliest().is_none());
+}
+
+#[test]
+fn test_schedule_in_named_zone() {
+    let tokyo = Zone::parse("Asia/Tokyo").unwrap();
+    let raw: BTreeMap<String, SpecialEntryConfig> =
+        BTreeMap::from([("9".into(), SpecialEntryConfig::Item("standup".into()))]);
+    let parsed = parse_special_entries(&raw).unwrap();
+
+    // 00:30 UTC is 09:30 in Tokyo
+    let now = Utc
+        .with_ymd_and_hms(2025, 3, 31, 0, 30, 0)
+        .unwrap()
+        .with_timezone(&tokyo);
+    assert_eq!(resolve_special(&parsed, now, None).unwrap().item, "standup");
+
+    let (start, end) = parsed[0].active_window(now, None).unwrap();
+    assert_eq!(
+        start.to_utc(),
+        Utc.with_ymd_and_hms(2025, 3, 31, 0, 0, 0).unwrap()
+    );
+    assert_eq!(
+        end.to_utc(),
+        Utc.with_ymd_and_hms(2025, 3, 31, 1, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_wait_time_in_half_hour_zone() {
+    let kolkata = Zone::parse("Asia/Kolkata").unwrap();
+
+    // 12:00 UTC is 17:30 in Kolkata, half way through the local hour
+    let now = Utc
+        .with_ymd_and_hms(2025, 3, 31, 12, 0, 0)
+        .unwrap()
+        .with_timezone(&kolkata);
+    assert_eq!(calc::wait_time(60.0, now), 1800);
+}